@@ -0,0 +1,106 @@
+use std::{fs, path::Path};
+
+use serde::Deserialize;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Unable to read {}: {source}", path.display())]
+    Io {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Unable to parse fixtures: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("Room {room} references unknown user {username}")]
+    UnknownUser { room: String, username: String },
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Fixtures {
+    #[serde(default)]
+    pub users: Vec<FixtureUser>,
+    #[serde(default)]
+    pub rooms: Vec<FixtureRoom>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FixtureUser {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FixtureRoom {
+    pub name: String,
+    pub members: Vec<String>,
+    #[serde(default)]
+    pub messages: Vec<FixtureMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FixtureMessage {
+    pub from: String,
+    pub content: String,
+    #[serde(default)]
+    pub read: bool,
+}
+
+impl Default for Fixtures {
+    fn default() -> Self {
+        let get_user = |username: &str| FixtureUser {
+            username: username.to_string(),
+            password: "password".to_string(),
+        };
+        let get_message = |from: &str, content: &str| FixtureMessage {
+            from: from.to_string(),
+            content: content.to_string(),
+            read: true,
+        };
+
+        Self {
+            users: vec![get_user("alice"), get_user("bob"), get_user("carol")],
+            rooms: vec![FixtureRoom {
+                name: "alice & bob".to_string(),
+                members: vec!["alice".to_string(), "bob".to_string()],
+                messages: vec![
+                    get_message("alice", "Hi Bob"),
+                    get_message("bob", "Hello Alice"),
+                ],
+            }],
+        }
+    }
+}
+
+impl Fixtures {
+    fn validate(&self) -> Result<(), Error> {
+        for room in &self.rooms {
+            let usernames = room
+                .members
+                .iter()
+                .chain(room.messages.iter().map(|message| &message.from));
+
+            for username in usernames {
+                if !self.users.iter().any(|user| &user.username == username) {
+                    return Err(Error::UnknownUser {
+                        room: room.name.clone(),
+                        username: username.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub fn load(path: &Path) -> Result<Fixtures, Error> {
+    let content = fs::read_to_string(path).map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    let fixtures: Fixtures = toml::from_str(&content)?;
+    fixtures.validate()?;
+
+    Ok(fixtures)
+}
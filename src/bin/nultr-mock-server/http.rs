@@ -0,0 +1,139 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, header},
+};
+use nultr_shared_lib::request::{
+    AuthenticatedUnexpectedErrorResponse, CreatePrivateRoomErrorResponse, CreatePrivateRoomRequest,
+    CreatePrivateRoomResponse, GetMessagesErrorResponse, GetMessagesRequest, GetMessagesResponse,
+    GetRoomsErrorResponse, GetRoomsRequest, GetRoomsResponse, GetUsersErrorResponse,
+    GetUsersRequest, GetUsersResponse, Identifier, LoginErrorResponse, LoginRequest, LoginResponse,
+    RegisterErrorResponse, RegisterRequest, RegisterResponse, UnexpectedErrorResponse,
+};
+use rust_api_kit::http::client::Response;
+
+use crate::{SharedState, protocol::ApiRequest, state::MockState};
+
+pub async fn login(
+    State(state): State<SharedState>,
+    ApiRequest(request): ApiRequest<LoginRequest>,
+) -> Response<LoginResponse, LoginErrorResponse, UnexpectedErrorResponse> {
+    let mut state = state.lock().await;
+
+    let user_id = match state.find_user(&request.username) {
+        Some(user) if user.password == request.password => user.id,
+        _ => return Response::Error(LoginErrorResponse::WrongCredentials),
+    };
+
+    let token = state.issue_token(user_id);
+
+    Response::Ok(LoginResponse { user_id, token })
+}
+
+pub async fn register(
+    State(state): State<SharedState>,
+    ApiRequest(request): ApiRequest<RegisterRequest>,
+) -> Response<RegisterResponse, RegisterErrorResponse, UnexpectedErrorResponse> {
+    let mut state = state.lock().await;
+
+    if state.find_user(&request.username).is_some() {
+        return Response::Error(RegisterErrorResponse::UserAlreadyExists);
+    }
+
+    let user_id = state.add_user(request.username, request.password);
+    let token = state.issue_token(user_id);
+
+    Response::Ok(RegisterResponse { user_id, token })
+}
+
+pub async fn get_users(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    ApiRequest(_): ApiRequest<GetUsersRequest>,
+) -> Response<GetUsersResponse, GetUsersErrorResponse, AuthenticatedUnexpectedErrorResponse> {
+    let state = state.lock().await;
+
+    if let Err(error) = authenticate(&state, &headers) {
+        return Response::UnexpectedError(error);
+    }
+
+    Response::Ok(GetUsersResponse(state.get_users()))
+}
+
+pub async fn get_rooms(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    ApiRequest(_): ApiRequest<GetRoomsRequest>,
+) -> Response<GetRoomsResponse, GetRoomsErrorResponse, AuthenticatedUnexpectedErrorResponse> {
+    let state = state.lock().await;
+
+    let user_id = match authenticate(&state, &headers) {
+        Ok(user_id) => user_id,
+        Err(error) => return Response::UnexpectedError(error),
+    };
+
+    Response::Ok(GetRoomsResponse(state.get_rooms(user_id)))
+}
+
+pub async fn get_messages(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    ApiRequest(request): ApiRequest<GetMessagesRequest>,
+) -> Response<GetMessagesResponse, GetMessagesErrorResponse, AuthenticatedUnexpectedErrorResponse> {
+    let state = state.lock().await;
+
+    let user_id = match authenticate(&state, &headers) {
+        Ok(user_id) => user_id,
+        Err(error) => return Response::UnexpectedError(error),
+    };
+
+    if !state.is_member(user_id, request.room_id) {
+        return Response::Error(GetMessagesErrorResponse::NotMemberOfRoom);
+    }
+
+    let messages = state.get_messages(request.room_id, request.page, request.page_size);
+
+    Response::Ok(GetMessagesResponse(messages))
+}
+
+pub async fn create_private_room(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    ApiRequest(request): ApiRequest<CreatePrivateRoomRequest>,
+) -> Response<
+    CreatePrivateRoomResponse,
+    CreatePrivateRoomErrorResponse,
+    AuthenticatedUnexpectedErrorResponse,
+> {
+    let mut state = state.lock().await;
+
+    let user_id = match authenticate(&state, &headers) {
+        Ok(user_id) => user_id,
+        Err(error) => return Response::UnexpectedError(error),
+    };
+
+    match state.get_or_create_private_room(user_id, request.receiver_user_id) {
+        Some(room) => Response::Ok(CreatePrivateRoomResponse {
+            id: room.id,
+            name: room.name.clone(),
+        }),
+        None => Response::Error(CreatePrivateRoomErrorResponse::UserNotFound),
+    }
+}
+
+pub fn get_bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+// rust-api-kit reads the body regardless of status, so a bad token is reported in the body
+fn authenticate(
+    state: &MockState,
+    headers: &HeaderMap,
+) -> Result<Identifier, AuthenticatedUnexpectedErrorResponse> {
+    get_bearer_token(headers)
+        .and_then(|token| state.authenticate(token))
+        .ok_or(AuthenticatedUnexpectedErrorResponse::InvalidToken)
+}
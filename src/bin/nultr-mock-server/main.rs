@@ -0,0 +1,159 @@
+mod fixtures;
+mod http;
+mod protocol;
+mod state;
+mod ws;
+
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
+
+use axum::{Router, routing::any};
+use clap::Parser;
+use nultr_shared_lib::request::{
+    CreatePrivateRoomRequest, GetMessagesRequest, GetRoomsRequest, GetUsersRequest, LoginRequest,
+    RegisterRequest,
+};
+use tokio::sync::Mutex;
+
+use state::MockState;
+
+/// In-memory stand-in for the nultr backend
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Args {
+    /// Address to listen on
+    #[arg(long, env = "NULTR_MOCK_ADDR", default_value = "127.0.0.1:3005")]
+    addr: SocketAddr,
+
+    /// TOML file with users, rooms and messages to start with
+    #[arg(long, env = "NULTR_MOCK_FIXTURES")]
+    fixtures: Option<PathBuf>,
+}
+
+pub type SharedState = Arc<Mutex<MockState>>;
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt::init();
+
+    let args = Args::parse();
+
+    let fixtures = match args.fixtures {
+        Some(path) => match fixtures::load(&path) {
+            Ok(fixtures) => fixtures,
+            Err(error) => {
+                eprintln!("Invalid fixtures: {error}");
+                std::process::exit(2);
+            }
+        },
+        None => fixtures::Fixtures::default(),
+    };
+
+    let state: SharedState = Arc::new(Mutex::new(MockState::new(fixtures)));
+
+    let router = get_router(state);
+
+    let listener = match tokio::net::TcpListener::bind(args.addr).await {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("Unable to listen on {}: {error}", args.addr);
+            std::process::exit(1);
+        }
+    };
+
+    tracing::info!("Mock server listening on {}", args.addr);
+
+    if let Err(error) = axum::serve(listener, router).await {
+        tracing::error!("Server error: {error}");
+    }
+}
+
+// Paths and methods come from the request definitions the client uses
+fn get_router(state: SharedState) -> Router {
+    rust_api_kit::generate_routes! {
+        LoginRequest => http::login,
+        RegisterRequest => http::register,
+        GetUsersRequest => http::get_users,
+        GetRoomsRequest => http::get_rooms,
+        GetMessagesRequest => http::get_messages,
+        CreatePrivateRoomRequest => http::create_private_room,
+    }
+    .route(protocol::WS_PATH, any(ws::upgrade))
+    .with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use nultr_shared_lib::request::{AuthenticatedUnexpectedErrorResponse, LoginErrorResponse};
+    use rust_api_kit::http::client::{HttpClient, UnexpectedHttpError};
+    use url::Url;
+
+    use super::*;
+
+    async fn get_client() -> HttpClient {
+        let state: SharedState =
+            Arc::new(Mutex::new(MockState::new(fixtures::Fixtures::default())));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(axum::serve(listener, get_router(state)).into_future());
+
+        HttpClient::new(Url::parse(&format!("http://{addr}")).unwrap())
+    }
+
+    fn get_login_request(password: &str) -> LoginRequest {
+        LoginRequest {
+            username: "alice".to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn client_logs_in_and_lists_rooms() {
+        use rust_api_kit::http::client::{AuthenticatedHttpClientTrait, BasicHttpClientTrait};
+
+        let client = get_client().await;
+
+        let login = BasicHttpClientTrait::request(&client, get_login_request("password"))
+            .await
+            .unwrap()
+            .unwrap();
+
+        let rooms =
+            AuthenticatedHttpClientTrait::request(&client, GetRoomsRequest {}, login.token).await;
+
+        assert!(matches!(rooms, Ok(Ok(_))));
+    }
+
+    #[tokio::test]
+    async fn wrong_password_is_an_expected_error() {
+        use rust_api_kit::http::client::BasicHttpClientTrait;
+
+        let client = get_client().await;
+
+        let login = client.request(get_login_request("wrong")).await;
+
+        assert!(matches!(
+            login,
+            Ok(Err(LoginErrorResponse::WrongCredentials))
+        ));
+    }
+
+    #[tokio::test]
+    async fn unknown_token_is_rejected() {
+        use rust_api_kit::http::client::AuthenticatedHttpClientTrait;
+
+        let client = get_client().await;
+
+        let rooms = client
+            .request(GetRoomsRequest {}, "unknown".to_string())
+            .await;
+
+        assert!(matches!(
+            rooms,
+            Err(UnexpectedHttpError::Api(
+                AuthenticatedUnexpectedErrorResponse::InvalidToken
+            ))
+        ));
+    }
+}
@@ -0,0 +1,42 @@
+//! Adapts shared-lib's request definitions to axum, so routes and bodies follow the client
+
+use axum::{
+    Json,
+    extract::{FromRequest, FromRequestParts, Query, Request},
+    http::Method,
+    response::{IntoResponse, Response},
+};
+use serde::de::DeserializeOwned;
+
+pub use nultr_shared_lib::request::{WsRequest, WsResponse};
+
+// The websocket url comes from the client profile rather than shared-lib,
+// this is the path used by the local profile in client-config.toml
+pub const WS_PATH: &str = "/ws";
+
+/// Request body decoded the way rust-api-kit encodes it: query string for GET, json otherwise
+pub struct ApiRequest<R>(pub R);
+
+impl<R, S> FromRequest<S> for ApiRequest<R>
+where
+    R: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if request.method() == Method::GET {
+            let (mut parts, _) = request.into_parts();
+
+            Query::<R>::from_request_parts(&mut parts, state)
+                .await
+                .map(|Query(request)| Self(request))
+                .map_err(IntoResponse::into_response)
+        } else {
+            Json::<R>::from_request(request, state)
+                .await
+                .map(|Json(request)| Self(request))
+                .map_err(IntoResponse::into_response)
+        }
+    }
+}
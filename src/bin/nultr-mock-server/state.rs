@@ -0,0 +1,384 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDateTime, Utc};
+use nultr_shared_lib::request::{
+    Identifier, MessageResponse, RoomResponse, UserResponse, UuidIdentifier,
+    WsMarkMessagesReadRequest, WsMessageRequest,
+};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::{fixtures::Fixtures, protocol::WsResponse};
+
+pub struct User {
+    pub id: Identifier,
+    pub username: String,
+    pub password: String,
+}
+
+pub struct Room {
+    pub id: Identifier,
+    pub name: String,
+    pub member_ids: Vec<Identifier>,
+}
+
+struct Message {
+    uuid: UuidIdentifier,
+    room_id: Identifier,
+    user_id: Identifier,
+    content: String,
+    created_at: NaiveDateTime,
+    read: bool,
+}
+
+impl From<&Message> for MessageResponse {
+    fn from(value: &Message) -> Self {
+        Self {
+            uuid: value.uuid,
+            room_id: value.room_id,
+            user_id: value.user_id,
+            content: value.content.clone(),
+            created_at: value.created_at,
+            read: value.read,
+        }
+    }
+}
+
+pub enum SendError {
+    NotMemberOfRoom,
+}
+
+pub type ConnectionId = u64;
+
+struct Connection {
+    id: ConnectionId,
+    sender: mpsc::UnboundedSender<WsResponse>,
+}
+
+pub struct MockState {
+    users: Vec<User>,
+    rooms: Vec<Room>,
+    messages: Vec<Message>,
+    tokens: HashMap<String, Identifier>,
+    connections: HashMap<Identifier, Vec<Connection>>,
+    next_id: Identifier,
+    next_connection_id: ConnectionId,
+}
+
+impl MockState {
+    pub fn new(fixtures: Fixtures) -> Self {
+        let mut state = Self {
+            users: Vec::new(),
+            rooms: Vec::new(),
+            messages: Vec::new(),
+            tokens: HashMap::new(),
+            connections: HashMap::new(),
+            next_id: 1,
+            next_connection_id: 1,
+        };
+
+        for user in fixtures.users {
+            state.add_user(user.username, user.password);
+        }
+
+        // Fixtures are validated on load, so every username resolves
+        let mut created_at = Utc::now().naive_utc();
+
+        for room in fixtures.rooms {
+            let member_ids = room
+                .members
+                .iter()
+                .filter_map(|username| state.find_user(username))
+                .map(|user| user.id)
+                .collect();
+            let room_id = state.add_room(room.name, member_ids);
+
+            for message in room.messages {
+                let Some(user_id) = state.find_user(&message.from).map(|user| user.id) else {
+                    continue;
+                };
+
+                created_at += chrono::Duration::seconds(1);
+
+                state.messages.push(Message {
+                    uuid: Uuid::new_v4(),
+                    room_id,
+                    user_id,
+                    content: message.content,
+                    created_at,
+                    read: message.read,
+                });
+            }
+        }
+
+        state
+    }
+
+    pub fn find_user(&self, username: &str) -> Option<&User> {
+        self.users.iter().find(|user| user.username == username)
+    }
+
+    pub fn add_user(&mut self, username: String, password: String) -> Identifier {
+        let id = self.get_next_id();
+
+        self.users.push(User {
+            id,
+            username,
+            password,
+        });
+
+        id
+    }
+
+    pub fn issue_token(&mut self, user_id: Identifier) -> String {
+        let token = Uuid::new_v4().to_string();
+
+        self.tokens.insert(token.clone(), user_id);
+
+        token
+    }
+
+    pub fn authenticate(&self, token: &str) -> Option<Identifier> {
+        self.tokens.get(token).copied()
+    }
+
+    pub fn get_users(&self) -> Vec<UserResponse> {
+        self.users
+            .iter()
+            .map(|user| UserResponse {
+                id: user.id,
+                username: user.username.clone(),
+            })
+            .collect()
+    }
+
+    pub fn get_rooms(&self, user_id: Identifier) -> Vec<RoomResponse> {
+        self.rooms
+            .iter()
+            .filter(|room| room.member_ids.contains(&user_id))
+            .map(|room| RoomResponse {
+                id: room.id,
+                name: room.name.clone(),
+            })
+            .collect()
+    }
+
+    pub fn is_member(&self, user_id: Identifier, room_id: Identifier) -> bool {
+        self.rooms
+            .iter()
+            .any(|room| room.id == room_id && room.member_ids.contains(&user_id))
+    }
+
+    // Pages count back from the newest message, like the real backend
+    pub fn get_messages(
+        &self,
+        room_id: Identifier,
+        page: u64,
+        page_size: u64,
+    ) -> Vec<MessageResponse> {
+        let room_messages: Vec<&Message> = self
+            .messages
+            .iter()
+            .filter(|message| message.room_id == room_id)
+            .collect();
+
+        let end = room_messages
+            .len()
+            .saturating_sub((page * page_size) as usize);
+        let start = end.saturating_sub(page_size as usize);
+
+        room_messages[start..end]
+            .iter()
+            .map(|message| MessageResponse::from(*message))
+            .collect()
+    }
+
+    pub fn get_or_create_private_room(
+        &mut self,
+        user_id: Identifier,
+        receiver_user_id: Identifier,
+    ) -> Option<&Room> {
+        let receiver_name = self
+            .users
+            .iter()
+            .find(|user| user.id == receiver_user_id)?
+            .username
+            .clone();
+
+        let existing_room_index = self.rooms.iter().position(|room| {
+            room.member_ids.len() == 2
+                && room.member_ids.contains(&user_id)
+                && room.member_ids.contains(&receiver_user_id)
+        });
+
+        let room_index = match existing_room_index {
+            Some(room_index) => room_index,
+            None => {
+                self.add_room(receiver_name, vec![user_id, receiver_user_id]);
+
+                self.rooms.len() - 1
+            }
+        };
+
+        self.rooms.get(room_index)
+    }
+
+    pub fn add_message(
+        &mut self,
+        user_id: Identifier,
+        connection_id: ConnectionId,
+        request: WsMessageRequest,
+    ) -> Result<(), SendError> {
+        if !self.is_member(user_id, request.room_id) {
+            return Err(SendError::NotMemberOfRoom);
+        }
+
+        // Retried sends reuse the uuid, so they are only acknowledged again
+        if !self
+            .messages
+            .iter()
+            .any(|message| message.uuid == request.uuid)
+        {
+            let message = Message {
+                uuid: request.uuid,
+                room_id: request.room_id,
+                user_id,
+                content: request.content,
+                created_at: Utc::now().naive_utc(),
+                read: false,
+            };
+
+            self.broadcast(
+                message.room_id,
+                connection_id,
+                WsResponse::Message(MessageResponse::from(&message)),
+            );
+            self.messages.push(message);
+        }
+
+        self.send_to_connection(
+            user_id,
+            connection_id,
+            WsResponse::MessageReceived(request.uuid),
+        );
+
+        Ok(())
+    }
+
+    pub fn mark_read(
+        &mut self,
+        user_id: Identifier,
+        connection_id: ConnectionId,
+        request: WsMarkMessagesReadRequest,
+    ) -> Result<(), SendError> {
+        if !self.is_member(user_id, request.room_id) {
+            return Err(SendError::NotMemberOfRoom);
+        }
+
+        let mut read_message_uuids = Vec::new();
+
+        for message in self.messages.iter_mut().filter(|message| {
+            message.room_id == request.room_id
+                && message.user_id != user_id
+                && request.message_uuids.contains(&message.uuid)
+        }) {
+            message.read = true;
+            read_message_uuids.push(message.uuid);
+        }
+
+        if !read_message_uuids.is_empty() {
+            self.broadcast(
+                request.room_id,
+                connection_id,
+                WsResponse::MessagesRead(WsMarkMessagesReadRequest {
+                    room_id: request.room_id,
+                    message_uuids: read_message_uuids,
+                }),
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn connect(
+        &mut self,
+        user_id: Identifier,
+        sender: mpsc::UnboundedSender<WsResponse>,
+    ) -> ConnectionId {
+        let id = self.next_connection_id;
+        self.next_connection_id += 1;
+
+        self.connections
+            .entry(user_id)
+            .or_default()
+            .push(Connection { id, sender });
+
+        id
+    }
+
+    pub fn disconnect(&mut self, user_id: Identifier, connection_id: ConnectionId) {
+        if let Some(connections) = self.connections.get_mut(&user_id) {
+            connections.retain(|connection| connection.id != connection_id);
+        }
+    }
+
+    pub fn send_to_connection(
+        &self,
+        user_id: Identifier,
+        connection_id: ConnectionId,
+        response: WsResponse,
+    ) {
+        let connection = self
+            .connections
+            .get(&user_id)
+            .into_iter()
+            .flatten()
+            .find(|connection| connection.id == connection_id);
+
+        if let Some(connection) = connection {
+            let _ = connection.sender.send(response);
+        }
+    }
+
+    // Other sessions of the same user get the update too, only the originating socket is skipped
+    fn broadcast(
+        &self,
+        room_id: Identifier,
+        except_connection_id: ConnectionId,
+        response: WsResponse,
+    ) {
+        let Some(room) = self.rooms.iter().find(|room| room.id == room_id) else {
+            return;
+        };
+
+        let connections = room
+            .member_ids
+            .iter()
+            .filter_map(|member_id| self.connections.get(member_id))
+            .flatten()
+            .filter(|connection| connection.id != except_connection_id);
+
+        for connection in connections {
+            let _ = connection.sender.send(response.clone());
+        }
+    }
+
+    fn add_room(&mut self, name: String, member_ids: Vec<Identifier>) -> Identifier {
+        let id = self.get_next_id();
+
+        self.rooms.push(Room {
+            id,
+            name,
+            member_ids,
+        });
+
+        id
+    }
+
+    fn get_next_id(&mut self) -> Identifier {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        id
+    }
+}
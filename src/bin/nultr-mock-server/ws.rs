@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{
+        Query, State, WebSocketUpgrade,
+        ws::{Message, WebSocket},
+    },
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use futures::{SinkExt, StreamExt};
+use nultr_shared_lib::request::Identifier;
+use tokio::sync::mpsc;
+
+use crate::{
+    SharedState, http,
+    protocol::{WsRequest, WsResponse},
+    state::SendError,
+};
+
+// The token is accepted from the header or the query, whichever the client sends
+pub async fn upgrade(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    let token = http::get_bearer_token(&headers).or(query.get("token").map(String::as_str));
+
+    let user_id = {
+        let state = state.lock().await;
+
+        token.and_then(|token| state.authenticate(token))
+    };
+
+    match user_id {
+        Some(user_id) => upgrade
+            .on_upgrade(move |socket| handle_socket(state, user_id, socket))
+            .into_response(),
+        None => StatusCode::UNAUTHORIZED.into_response(),
+    }
+}
+
+async fn handle_socket(state: SharedState, user_id: Identifier, socket: WebSocket) {
+    let (mut socket_sender, mut socket_receiver) = socket.split();
+    let (sender, mut receiver) = mpsc::unbounded_channel::<WsResponse>();
+
+    let connection_id = state.lock().await.connect(user_id, sender);
+
+    tracing::info!("User {user_id} connected");
+
+    let send_task = tokio::spawn(async move {
+        while let Some(response) = receiver.recv().await {
+            let payload = match serde_json::to_string(&response) {
+                Ok(payload) => payload,
+                Err(error) => {
+                    tracing::error!("Serialization error: {error}");
+
+                    continue;
+                }
+            };
+
+            if socket_sender
+                .send(Message::Text(payload.into()))
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    while let Some(Ok(message)) = socket_receiver.next().await {
+        let Message::Text(payload) = message else {
+            continue;
+        };
+
+        let mut state = state.lock().await;
+
+        let result = match serde_json::from_str::<WsRequest>(&payload) {
+            Ok(WsRequest::Message(request)) => state.add_message(user_id, connection_id, request),
+            Ok(WsRequest::MessagesRead(request)) => {
+                state.mark_read(user_id, connection_id, request)
+            }
+            Err(error) => {
+                tracing::error!("Wrong request format: {error}");
+                state.send_to_connection(user_id, connection_id, WsResponse::WrongRequestFormat);
+
+                continue;
+            }
+        };
+
+        if let Err(SendError::NotMemberOfRoom) = result {
+            state.send_to_connection(user_id, connection_id, WsResponse::NotMemberOfRoom);
+        }
+    }
+
+    send_task.abort();
+    state.lock().await.disconnect(user_id, connection_id);
+
+    tracing::info!("User {user_id} disconnected");
+}
@@ -0,0 +1,39 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use nultr_shared_lib::request::Identifier;
+use url::Url;
+
+/// Every option can also be set through its NULTR_* environment variable,
+/// which in turn takes precedence over the config file
+#[derive(Debug, Parser)]
+#[command(name = "nultr", version, about = "Nultr chat client")]
+pub struct Args {
+    /// Path to the config file
+    #[arg(long, env = "NULTR_CONFIG", default_value = "client-config.toml")]
+    pub config: PathBuf,
+
+    /// Override the http url of the selected server profile
+    #[arg(long, env = "NULTR_HTTP_URL")]
+    pub http_url: Option<Url>,
+
+    /// Override the websocket url of the selected server profile
+    #[arg(long, env = "NULTR_WS_URL")]
+    pub ws_url: Option<Url>,
+
+    /// One of error, warn, info, debug, trace
+    #[arg(long, env = "NULTR_LOG_LEVEL")]
+    pub log_level: Option<tracing::Level>,
+
+    /// Server profile preselected on the login screen
+    #[arg(long, env = "NULTR_PROFILE")]
+    pub profile: Option<String>,
+
+    /// Room opened once the first account has loaded its rooms
+    #[arg(long, env = "NULTR_OPEN_ROOM")]
+    pub open_room: Option<Identifier>,
+}
+
+pub fn parse() -> Args {
+    Args::parse()
+}
@@ -0,0 +1,461 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+
+use nultr_shared_lib::request::Identifier;
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use toml_edit::{ArrayOfTables, DocumentMut, Item, Table, value};
+use url::Url;
+
+use crate::cli;
+
+const DEFAULT_PROFILE_NAME: &str = "default";
+const DEFAULT_LOG_LEVEL: tracing::Level = tracing::Level::ERROR;
+const DEFAULT_FONT_SIZE: u16 = 16;
+
+pub const MIN_FONT_SIZE: u16 = 10;
+pub const MAX_FONT_SIZE: u16 = 32;
+pub const HTTP_URL_SCHEMES: &[&str] = &["http", "https"];
+pub const WS_URL_SCHEMES: &[&str] = &["ws", "wss"];
+
+static CONFIG: OnceCell<RwLock<Arc<Config>>> = OnceCell::new();
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Cannot read {}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("Cannot parse {}: {source}", path.display())]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("Cannot edit {}: {source}", path.display())]
+    Edit {
+        path: PathBuf,
+        source: toml_edit::TomlError,
+    },
+    #[error("Cannot write {}: {source}", path.display())]
+    Write { path: PathBuf, source: io::Error },
+    #[error("No server profiles configured, add [[profiles]] or pass --http-url and --ws-url")]
+    NoProfiles,
+    #[error("Duplicate server profile: {0}")]
+    DuplicateProfile(String),
+    #[error("Unknown server profile: {0}")]
+    UnknownProfile(String),
+    #[error("Server profile {profile}: {source}")]
+    InvalidUrl { profile: String, source: UrlError },
+    #[error("Invalid log level: {0}")]
+    InvalidLogLevel(String),
+    #[error("Font size must be between {MIN_FONT_SIZE} and {MAX_FONT_SIZE}, got {0}")]
+    InvalidFontSize(u16),
+    #[error("Config is already initialized")]
+    AlreadyInitialized,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UrlError {
+    #[error("Invalid url: {0}")]
+    Parse(#[from] url::ParseError),
+    #[error("Url scheme must be one of: {}", .0.join(", "))]
+    Scheme(&'static [&'static str]),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ServerProfile {
+    pub name: String,
+    pub http_url: Url,
+    pub ws_url: Url,
+}
+
+impl ServerProfile {
+    fn validate(&self) -> Result<(), Error> {
+        check_url_scheme(&self.http_url, HTTP_URL_SCHEMES)
+            .and_then(|_| check_url_scheme(&self.ws_url, WS_URL_SCHEMES))
+            .map_err(|source| Error::InvalidUrl {
+                profile: self.name.clone(),
+                source,
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeName {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl ThemeName {
+    pub const ALL: [ThemeName; 2] = [ThemeName::Dark, ThemeName::Light];
+
+    fn get_key(&self) -> &'static str {
+        match self {
+            ThemeName::Dark => "dark",
+            ThemeName::Light => "light",
+        }
+    }
+}
+
+impl fmt::Display for ThemeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeName::Dark => write!(f, "Dark"),
+            ThemeName::Light => write!(f, "Light"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Notifications {
+    #[serde(default = "get_true")]
+    pub show_unread_badges: bool,
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self {
+            show_unread_badges: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub path: PathBuf,
+    pub profiles: Vec<ServerProfile>,
+    pub default_profile_name: String,
+    pub log_level: tracing::Level,
+    pub theme: ThemeName,
+    pub font_size: u16,
+    pub notifications: Notifications,
+    pub open_room_id: Option<Identifier>,
+}
+
+impl Config {
+    pub fn get_profile(&self, name: &str) -> Option<&ServerProfile> {
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    pub fn get_default_profile(&self) -> &ServerProfile {
+        self.get_profile(&self.default_profile_name)
+            .unwrap_or(&self.profiles[0])
+    }
+
+    pub fn get_profile_names(&self) -> Vec<String> {
+        self.profiles
+            .iter()
+            .map(|profile| profile.name.clone())
+            .collect()
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if self.profiles.is_empty() {
+            return Err(Error::NoProfiles);
+        }
+
+        for (index, profile) in self.profiles.iter().enumerate() {
+            if self.profiles[..index]
+                .iter()
+                .any(|other| other.name == profile.name)
+            {
+                return Err(Error::DuplicateProfile(profile.name.clone()));
+            }
+
+            profile.validate()?;
+        }
+
+        if !(MIN_FONT_SIZE..=MAX_FONT_SIZE).contains(&self.font_size) {
+            return Err(Error::InvalidFontSize(self.font_size));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct RawConfig {
+    #[serde(default)]
+    profiles: Vec<ServerProfile>,
+    // Single server configs written before profiles existed
+    http_url: Option<Url>,
+    ws_url: Option<Url>,
+    default_profile: Option<String>,
+    log_level: Option<String>,
+    #[serde(default)]
+    theme: ThemeName,
+    font_size: Option<u16>,
+    #[serde(default)]
+    notifications: Notifications,
+}
+
+pub fn init(args: cli::Args) -> Result<Arc<Config>, Error> {
+    let config = Arc::new(load(args)?);
+
+    CONFIG
+        .set(RwLock::new(config.clone()))
+        .map_err(|_| Error::AlreadyInitialized)?;
+
+    Ok(config)
+}
+
+pub fn get_config() -> Arc<Config> {
+    CONFIG
+        .get()
+        .expect("Config is used before config::init")
+        .read()
+        .expect("Config lock is poisoned")
+        .clone()
+}
+
+pub fn parse_url(url: &str, schemes: &'static [&'static str]) -> Result<Url, UrlError> {
+    let url = Url::parse(url.trim())?;
+
+    check_url_scheme(&url, schemes)?;
+
+    Ok(url)
+}
+
+/// Values edited on the settings screen. Only these are written back to the file,
+/// so command line and env overrides never end up in it
+#[derive(Debug, Clone)]
+pub struct SettingsChanges {
+    pub profiles: Vec<ServerProfile>,
+    pub log_level: Option<tracing::Level>,
+    pub theme: ThemeName,
+    pub font_size: u16,
+    pub notifications: Notifications,
+}
+
+/// Applies the changes and writes them to the config file, keeping keys and comments
+/// this client doesn't know about
+pub fn save(changes: SettingsChanges) -> Result<Arc<Config>, Error> {
+    let mut config = (*get_config()).clone();
+
+    for changed_profile in &changes.profiles {
+        if let Some(profile) = config
+            .profiles
+            .iter_mut()
+            .find(|profile| profile.name == changed_profile.name)
+        {
+            *profile = changed_profile.clone();
+        }
+    }
+
+    if let Some(log_level) = changes.log_level {
+        config.log_level = log_level;
+    }
+
+    config.theme = changes.theme;
+    config.font_size = changes.font_size;
+    config.notifications = changes.notifications.clone();
+
+    config.validate()?;
+
+    let content = match fs::read_to_string(&config.path) {
+        Ok(content) => content,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(source) => {
+            return Err(Error::Io {
+                path: config.path.clone(),
+                source,
+            });
+        }
+    };
+
+    let mut document: DocumentMut = content.parse().map_err(|source| Error::Edit {
+        path: config.path.clone(),
+        source,
+    })?;
+
+    if let Some(log_level) = changes.log_level {
+        document["log_level"] = value(log_level.to_string().to_lowercase());
+    }
+
+    document["theme"] = value(changes.theme.get_key());
+    document["font_size"] = value(i64::from(changes.font_size));
+    document["notifications"]["show_unread_badges"] =
+        value(changes.notifications.show_unread_badges);
+
+    for profile in &changes.profiles {
+        write_profile(&mut document, profile);
+    }
+
+    write_atomically(&config.path, &document.to_string()).map_err(|source| Error::Write {
+        path: config.path.clone(),
+        source,
+    })?;
+
+    let config = Arc::new(config);
+
+    *CONFIG
+        .get()
+        .expect("Config is used before config::init")
+        .write()
+        .expect("Config lock is poisoned") = config.clone();
+
+    Ok(config)
+}
+
+// Command line arguments (and their env variables) win over the file
+fn load(args: cli::Args) -> Result<Config, Error> {
+    let raw_config = read_raw_config(&args)?;
+
+    let mut profiles = raw_config.profiles;
+
+    if let (Some(http_url), Some(ws_url)) = (raw_config.http_url, raw_config.ws_url) {
+        profiles.insert(
+            0,
+            ServerProfile {
+                name: DEFAULT_PROFILE_NAME.to_string(),
+                http_url,
+                ws_url,
+            },
+        );
+    }
+
+    if profiles.is_empty()
+        && let (Some(http_url), Some(ws_url)) = (&args.http_url, &args.ws_url)
+    {
+        profiles.push(ServerProfile {
+            name: DEFAULT_PROFILE_NAME.to_string(),
+            http_url: http_url.clone(),
+            ws_url: ws_url.clone(),
+        });
+    }
+
+    if profiles.is_empty() {
+        return Err(Error::NoProfiles);
+    }
+
+    let default_profile_name = args
+        .profile
+        .or(raw_config.default_profile)
+        .unwrap_or_else(|| profiles[0].name.clone());
+
+    let default_profile = profiles
+        .iter_mut()
+        .find(|profile| profile.name == default_profile_name)
+        .ok_or_else(|| Error::UnknownProfile(default_profile_name.clone()))?;
+
+    if let Some(http_url) = args.http_url {
+        default_profile.http_url = http_url;
+    }
+
+    if let Some(ws_url) = args.ws_url {
+        default_profile.ws_url = ws_url;
+    }
+
+    let log_level = match (args.log_level, raw_config.log_level) {
+        (Some(log_level), _) => log_level,
+        (None, Some(log_level)) => {
+            tracing::Level::from_str(&log_level).map_err(|_| Error::InvalidLogLevel(log_level))?
+        }
+        (None, None) => DEFAULT_LOG_LEVEL,
+    };
+
+    let config = Config {
+        path: args.config,
+        profiles,
+        default_profile_name,
+        log_level,
+        theme: raw_config.theme,
+        font_size: raw_config.font_size.unwrap_or(DEFAULT_FONT_SIZE),
+        notifications: raw_config.notifications,
+        open_room_id: args.open_room,
+    };
+
+    config.validate()?;
+
+    Ok(config)
+}
+
+fn read_raw_config(args: &cli::Args) -> Result<RawConfig, Error> {
+    let content = match fs::read_to_string(&args.config) {
+        Ok(content) => content,
+        // A config file is optional when the server is given on the command line
+        Err(error)
+            if error.kind() == io::ErrorKind::NotFound
+                && args.http_url.is_some()
+                && args.ws_url.is_some() =>
+        {
+            return Ok(RawConfig::default());
+        }
+        Err(source) => {
+            return Err(Error::Io {
+                path: args.config.clone(),
+                source,
+            });
+        }
+    };
+
+    toml::from_str(&content).map_err(|source| Error::Parse {
+        path: args.config.clone(),
+        source,
+    })
+}
+
+fn write_profile(document: &mut DocumentMut, profile: &ServerProfile) {
+    let profile_table = document
+        .get_mut("profiles")
+        .and_then(Item::as_array_of_tables_mut)
+        .and_then(|profile_tables| {
+            profile_tables.iter_mut().find(|profile_table| {
+                profile_table.get("name").and_then(Item::as_str) == Some(profile.name.as_str())
+            })
+        });
+
+    match profile_table {
+        Some(profile_table) => {
+            profile_table["http_url"] = value(profile.http_url.as_str());
+            profile_table["ws_url"] = value(profile.ws_url.as_str());
+        }
+        // The default profile may come from the legacy top-level keys
+        None if profile.name == DEFAULT_PROFILE_NAME => {
+            document["http_url"] = value(profile.http_url.as_str());
+            document["ws_url"] = value(profile.ws_url.as_str());
+        }
+        None => {
+            let mut profile_table = Table::new();
+            profile_table["name"] = value(profile.name.as_str());
+            profile_table["http_url"] = value(profile.http_url.as_str());
+            profile_table["ws_url"] = value(profile.ws_url.as_str());
+
+            match document
+                .get_mut("profiles")
+                .and_then(Item::as_array_of_tables_mut)
+            {
+                Some(profile_tables) => profile_tables.push(profile_table),
+                None => {
+                    let mut profile_tables = ArrayOfTables::new();
+                    profile_tables.push(profile_table);
+
+                    document["profiles"] = Item::ArrayOfTables(profile_tables);
+                }
+            }
+        }
+    }
+}
+
+fn write_atomically(path: &Path, content: &str) -> io::Result<()> {
+    let temp_path = path.with_extension("tmp");
+
+    fs::write(&temp_path, content)?;
+    fs::rename(temp_path, path)
+}
+
+fn check_url_scheme(url: &Url, schemes: &'static [&'static str]) -> Result<(), UrlError> {
+    if schemes.contains(&url.scheme()) {
+        Ok(())
+    } else {
+        Err(UrlError::Scheme(schemes))
+    }
+}
+
+fn get_true() -> bool {
+    true
+}
@@ -0,0 +1,186 @@
+use std::{collections::VecDeque, fmt::Debug, path::PathBuf, sync::Mutex, time::Instant};
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use rust_api_kit::http::client::UnexpectedHttpError;
+use serde::Serialize;
+
+use crate::storage;
+
+const MAX_ENTRIES: usize = 1000;
+const REDACTED: &str = "<redacted>";
+
+static LOG: Lazy<Mutex<Log>> = Lazy::new(|| Mutex::new(Log::default()));
+
+// Matches quoted values of token, password and passphrase fields in Debug output
+static SECRET_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)(\w*(?:token|password|passphrase)\w*\s*[:=]\s*(?:\w+\s*[({]\s*)?)"[^"]*""#)
+        .expect("Secret regex is valid")
+});
+
+#[derive(Default)]
+struct Log {
+    entries: VecDeque<Entry>,
+    next_id: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    Http,
+    WsSent,
+    WsReceived,
+}
+
+/// One captured request or websocket frame, secrets are redacted before it is stored
+#[derive(Debug, Clone, Serialize)]
+pub struct Entry {
+    pub id: u64,
+    pub at: DateTime<Utc>,
+    pub kind: EntryKind,
+    pub name: String,
+    pub status: String,
+    pub duration_ms: Option<u128>,
+    pub request: Option<String>,
+    pub response: Option<String>,
+}
+
+impl Entry {
+    pub fn is_failure(&self) -> bool {
+        self.status != "ok"
+    }
+}
+
+pub async fn inspect_http<R, F, Fut, T, E, U>(
+    request: R,
+    send: F,
+) -> Result<Result<T, E>, UnexpectedHttpError<U>>
+where
+    R: Debug,
+    F: FnOnce(R) -> Fut,
+    Fut: Future<Output = Result<Result<T, E>, UnexpectedHttpError<U>>>,
+    T: Debug,
+    E: Debug,
+    U: Debug,
+{
+    let name = get_type_name::<R>();
+    let request_body = format!("{request:#?}");
+    let started_at = Instant::now();
+
+    let result = send(request).await;
+
+    let status = match &result {
+        Ok(Ok(_)) => "ok".to_string(),
+        Ok(Err(_)) => "api error".to_string(),
+        Err(error) => format!("failed: {}", get_variant_name(&format!("{error:?}"))),
+    };
+
+    push(
+        EntryKind::Http,
+        name.to_string(),
+        status,
+        Some(started_at.elapsed().as_millis()),
+        Some(request_body),
+        Some(format!("{result:#?}")),
+    );
+
+    result
+}
+
+pub fn record_ws_sent(event: &impl Debug) {
+    let body = format!("{event:#?}");
+
+    push(
+        EntryKind::WsSent,
+        get_variant_name(&body).to_string(),
+        "ok".to_string(),
+        None,
+        Some(body),
+        None,
+    );
+}
+
+pub fn record_ws_received<T: Debug, E: Debug>(result: &Result<T, E>) {
+    let (status, body) = match result {
+        Ok(event) => ("ok".to_string(), format!("{event:#?}")),
+        Err(error) => ("error".to_string(), format!("{error:#?}")),
+    };
+
+    push(
+        EntryKind::WsReceived,
+        get_variant_name(&body).to_string(),
+        status,
+        None,
+        None,
+        Some(body),
+    );
+}
+
+pub fn get_entries() -> Vec<Entry> {
+    lock_log().entries.iter().cloned().collect()
+}
+
+pub fn clear() {
+    lock_log().entries.clear();
+}
+
+pub fn export(entries: &[Entry]) -> Result<PathBuf, storage::Error> {
+    let file_name = format!("network-log-{}.json", Utc::now().format("%Y%m%d-%H%M%S"));
+
+    storage::save(&file_name, entries)?;
+
+    storage::get_path(&file_name)
+}
+
+fn push(
+    kind: EntryKind,
+    name: String,
+    status: String,
+    duration_ms: Option<u128>,
+    request: Option<String>,
+    response: Option<String>,
+) {
+    let mut log = lock_log();
+
+    let entry = Entry {
+        id: log.next_id,
+        at: Utc::now(),
+        kind,
+        name,
+        status,
+        duration_ms,
+        request: request.map(|body| redact(&body)),
+        response: response.map(|body| redact(&body)),
+    };
+
+    log.next_id += 1;
+    log.entries.push_back(entry);
+
+    if log.entries.len() > MAX_ENTRIES {
+        log.entries.pop_front();
+    }
+}
+
+// A panic while holding the lock only loses log entries, so poisoning is ignored
+fn lock_log() -> std::sync::MutexGuard<'static, Log> {
+    LOG.lock().unwrap_or_else(|error| error.into_inner())
+}
+
+fn redact(body: &str) -> String {
+    SECRET_REGEX
+        .replace_all(body, format!("${{1}}\"{REDACTED}\""))
+        .into_owned()
+}
+
+fn get_type_name<T>() -> &'static str {
+    let type_name = std::any::type_name::<T>();
+
+    type_name.rsplit("::").next().unwrap_or(type_name)
+}
+
+fn get_variant_name(body: &str) -> &str {
+    body.split(|char: char| !char.is_alphanumeric() && char != '_')
+        .next()
+        .unwrap_or(body)
+}
@@ -0,0 +1,26 @@
+use once_cell::sync::OnceCell;
+use tracing_subscriber::{Registry, filter::LevelFilter, fmt, prelude::*, reload};
+
+static LEVEL_HANDLE: OnceCell<reload::Handle<LevelFilter, Registry>> = OnceCell::new();
+
+pub fn init(level: tracing::Level) {
+    let (level_filter, level_handle) = reload::Layer::new(LevelFilter::from_level(level));
+
+    tracing_subscriber::registry()
+        .with(level_filter)
+        .with(fmt::layer())
+        .init();
+
+    let _ = LEVEL_HANDLE.set(level_handle);
+}
+
+/// Changes the log level without restarting the app
+pub fn set_level(level: tracing::Level) {
+    let Some(level_handle) = LEVEL_HANDLE.get() else {
+        return;
+    };
+
+    if let Err(error) = level_handle.reload(LevelFilter::from_level(level)) {
+        tracing::error!("Unable to change log level: {error}");
+    }
+}
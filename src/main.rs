@@ -1,16 +1,35 @@
+mod cli;
+mod config;
+mod inspector;
+mod logging;
+mod outbox;
+mod server;
+mod session;
+mod storage;
 mod ui;
 mod util;
-use nultr_client_lib::config;
+mod vault;
 use ui::Ui;
 
 pub fn main() -> iced::Result {
-    tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::ERROR)
-        .init();
+    let args = cli::parse();
 
-    config::get_variables();
+    let config = match config::init(args) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("Invalid configuration: {error}");
+            std::process::exit(2);
+        }
+    };
 
+    logging::init(config.log_level);
+
+    // Font size is only read on startup, the rest of the settings apply live
     iced::application("Nultr", Ui::update, Ui::view)
         .subscription(Ui::subscription)
-        .run()
+        .settings(iced::Settings {
+            default_text_size: f32::from(config.font_size).into(),
+            ..iced::Settings::default()
+        })
+        .run_with(Ui::new)
 }
@@ -0,0 +1,134 @@
+use chrono::NaiveDateTime;
+use nultr_shared_lib::request::{Identifier, UuidIdentifier, WsMessageRequest};
+use serde::{Deserialize, Serialize};
+
+use crate::{session::Session, storage};
+
+const OUTBOX_FILE_NAME: &str = "outbox.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxMessage {
+    pub uuid: UuidIdentifier,
+    pub room_id: Identifier,
+    pub content: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<&OutboxMessage> for WsMessageRequest {
+    fn from(value: &OutboxMessage) -> Self {
+        Self {
+            uuid: value.uuid,
+            room_id: value.room_id,
+            content: value.content.clone(),
+        }
+    }
+}
+
+/// Messages not yet acknowledged by the server, kept in send order
+#[derive(Debug, Clone)]
+pub struct Outbox {
+    profile_name: String,
+    user_id: Identifier,
+    messages: Vec<OutboxMessage>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredOutbox {
+    profile_name: String,
+    user_id: Identifier,
+    messages: Vec<OutboxMessage>,
+}
+
+impl StoredOutbox {
+    fn is_owned_by(&self, outbox: &Outbox) -> bool {
+        self.profile_name == outbox.profile_name && self.user_id == outbox.user_id
+    }
+}
+
+impl Outbox {
+    pub fn load(session: &Session) -> Self {
+        let mut outbox = Self {
+            profile_name: session.profile_name.clone(),
+            user_id: session.user_data.user_id,
+            messages: Vec::new(),
+        };
+
+        if let Some(stored_outbox) = load_stored()
+            .into_iter()
+            .find(|stored| stored.is_owned_by(&outbox))
+        {
+            outbox.messages = stored_outbox.messages;
+        }
+
+        outbox
+    }
+
+    pub fn get_messages(&self) -> &[OutboxMessage] {
+        &self.messages
+    }
+
+    pub fn get_room_messages(&self, room_id: Identifier) -> impl Iterator<Item = &OutboxMessage> {
+        self.messages
+            .iter()
+            .filter(move |message| message.room_id == room_id)
+    }
+
+    pub fn contains(&self, uuid: UuidIdentifier) -> bool {
+        self.messages.iter().any(|message| message.uuid == uuid)
+    }
+
+    pub fn push(&mut self, message: OutboxMessage) {
+        if self.contains(message.uuid) {
+            return;
+        }
+
+        self.messages.push(message);
+        self.store();
+    }
+
+    pub fn remove(&mut self, uuids: &[UuidIdentifier]) {
+        let messages_count = self.messages.len();
+
+        self.messages
+            .retain(|message| !uuids.contains(&message.uuid));
+
+        if self.messages.len() != messages_count {
+            self.store();
+        }
+    }
+
+    fn store(&self) {
+        let mut stored_outboxes = load_stored();
+
+        stored_outboxes.retain(|stored| !stored.is_owned_by(self));
+
+        if !self.messages.is_empty() {
+            stored_outboxes.push(StoredOutbox {
+                profile_name: self.profile_name.clone(),
+                user_id: self.user_id,
+                messages: self.messages.clone(),
+            });
+        }
+
+        let result = if stored_outboxes.is_empty() {
+            storage::remove(OUTBOX_FILE_NAME)
+        } else {
+            storage::save(OUTBOX_FILE_NAME, &stored_outboxes)
+        };
+
+        if let Err(error) = result {
+            tracing::error!("Unable to save outbox: {error}");
+        }
+    }
+}
+
+fn load_stored() -> Vec<StoredOutbox> {
+    match storage::load::<Vec<StoredOutbox>>(OUTBOX_FILE_NAME) {
+        Ok(stored_outboxes) => stored_outboxes.unwrap_or_default(),
+        Err(error) => {
+            tracing::error!("Unable to load outbox: {error}");
+
+            Vec::new()
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::{fmt, sync::Arc};
+
+use rust_api_kit::http::client::HttpClient;
+
+use crate::config::{self, ServerProfile};
+
+pub struct Server {
+    pub profile: ServerProfile,
+    pub http_client: HttpClient,
+}
+
+impl Server {
+    pub fn new(profile: ServerProfile) -> Arc<Self> {
+        let http_client = HttpClient::new(profile.http_url.clone());
+
+        Arc::new(Self {
+            profile,
+            http_client,
+        })
+    }
+
+    pub fn from_profile_name(name: &str) -> Option<Arc<Self>> {
+        config::get_config()
+            .get_profile(name)
+            .cloned()
+            .map(Self::new)
+    }
+
+    pub fn get_default() -> Arc<Self> {
+        Self::new(config::get_config().get_default_profile().clone())
+    }
+}
+
+impl fmt::Debug for Server {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Server")
+            .field("profile", &self.profile)
+            .finish()
+    }
+}
@@ -0,0 +1,107 @@
+use nultr_shared_lib::request::{AuthUserData, Identifier};
+use rust_api_kit::http::client::BearerToken;
+use serde::{Deserialize, Serialize};
+
+use crate::{config, storage};
+
+const SESSIONS_FILE_NAME: &str = "sessions.json";
+
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub profile_name: String,
+    pub username: String,
+    pub user_data: AuthUserData,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredSession {
+    #[serde(default = "get_default_profile_name")]
+    profile_name: String,
+    user_id: Identifier,
+    username: String,
+    token: BearerToken,
+}
+
+impl Session {
+    pub fn is_same_account(&self, other: &Session) -> bool {
+        self.profile_name == other.profile_name && self.user_data.user_id == other.user_data.user_id
+    }
+}
+
+impl StoredSession {
+    fn is_same_account(&self, session: &Session) -> bool {
+        self.profile_name == session.profile_name && self.user_id == session.user_data.user_id
+    }
+}
+
+impl From<StoredSession> for Session {
+    fn from(value: StoredSession) -> Self {
+        Self {
+            profile_name: value.profile_name,
+            username: value.username,
+            user_data: AuthUserData {
+                user_id: value.user_id,
+                token: value.token,
+            },
+        }
+    }
+}
+
+impl From<&Session> for StoredSession {
+    fn from(value: &Session) -> Self {
+        Self {
+            profile_name: value.profile_name.clone(),
+            user_id: value.user_data.user_id,
+            username: value.username.clone(),
+            token: value.user_data.token.clone(),
+        }
+    }
+}
+
+pub fn load() -> Vec<Session> {
+    load_stored().into_iter().map(Session::from).collect()
+}
+
+pub fn save(session: &Session) {
+    let mut sessions = load_stored();
+
+    sessions.retain(|stored| !stored.is_same_account(session));
+    sessions.push(StoredSession::from(session));
+
+    store(&sessions);
+}
+
+pub fn forget(session: &Session) {
+    let mut sessions = load_stored();
+
+    sessions.retain(|stored| !stored.is_same_account(session));
+
+    if sessions.is_empty() {
+        if let Err(error) = storage::remove(SESSIONS_FILE_NAME) {
+            tracing::error!("Unable to remove sessions: {error}");
+        }
+    } else {
+        store(&sessions);
+    }
+}
+
+fn get_default_profile_name() -> String {
+    config::get_config().get_default_profile().name.clone()
+}
+
+fn load_stored() -> Vec<StoredSession> {
+    match storage::load::<Vec<StoredSession>>(SESSIONS_FILE_NAME) {
+        Ok(sessions) => sessions.unwrap_or_default(),
+        Err(error) => {
+            tracing::error!("Unable to load sessions: {error}");
+
+            Vec::new()
+        }
+    }
+}
+
+fn store(sessions: &[StoredSession]) {
+    if let Err(error) = storage::save(SESSIONS_FILE_NAME, sessions) {
+        tracing::error!("Unable to save sessions: {error}");
+    }
+}
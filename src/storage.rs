@@ -0,0 +1,74 @@
+use std::{fs, path::PathBuf};
+
+use serde::{Serialize, de::DeserializeOwned};
+
+const APP_DIR_NAME: &str = "nultr";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Data directory is not available")]
+    DataDirNotFound,
+    #[error("Io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+pub fn get_path(file_name: &str) -> Result<PathBuf, Error> {
+    let dir = dirs::data_dir()
+        .ok_or(Error::DataDirNotFound)?
+        .join(APP_DIR_NAME);
+
+    fs::create_dir_all(&dir)?;
+
+    Ok(dir.join(file_name))
+}
+
+pub fn load<T: DeserializeOwned>(file_name: &str) -> Result<Option<T>, Error> {
+    let path = get_path(file_name)?;
+
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(path)?;
+
+    Ok(Some(serde_json::from_str(&content)?))
+}
+
+pub fn save<T: Serialize + ?Sized>(file_name: &str, value: &T) -> Result<(), Error> {
+    let path = get_path(file_name)?;
+    let temp_path = path.with_extension("tmp");
+    let content = serde_json::to_string_pretty(value)?;
+
+    // Write aside and rename so an interrupted save never leaves a truncated file
+    fs::write(&temp_path, content)?;
+    restrict_permissions(&temp_path)?;
+    fs::rename(temp_path, path)?;
+
+    Ok(())
+}
+
+pub fn remove(file_name: &str) -> Result<(), Error> {
+    let path = get_path(file_name)?;
+
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn restrict_permissions(path: &PathBuf) -> Result<(), Error> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_: &PathBuf) -> Result<(), Error> {
+    Ok(())
+}
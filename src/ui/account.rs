@@ -0,0 +1,175 @@
+use iced::Task;
+use nultr_client_lib::ws;
+
+use crate::{
+    inspector,
+    session::{self, Session},
+};
+
+use super::{
+    AccountId, AuthState, Event, Ui, WidgetErrorEvent, chat, error_popup, login_form, reauth_dialog,
+};
+
+pub struct Account {
+    pub id: AccountId,
+    pub session: Session,
+    pub chat: chat::State,
+    pub reauth_dialog: Option<reauth_dialog::State>,
+    pub paused_chat_events: Vec<chat::Event>,
+}
+
+impl Account {
+    pub fn new(id: AccountId, session: Session, chat: chat::State) -> Self {
+        Self {
+            id,
+            session,
+            chat,
+            reauth_dialog: None,
+            paused_chat_events: Vec::new(),
+        }
+    }
+}
+
+impl Ui {
+    pub(super) fn get_account_mut(&mut self, account_id: AccountId) -> Option<&mut Account> {
+        self.accounts
+            .iter_mut()
+            .find(|account| account.id == account_id)
+    }
+
+    pub(super) fn account_update(&mut self, account_id: AccountId, event: Event) -> Task<Event> {
+        let chat = self.chat.clone();
+        let reauth_dialog = self.reauth_dialog.clone();
+
+        let Some(account) = self.get_account_mut(account_id) else {
+            return Task::none();
+        };
+
+        if let Event::FromWs(result) = &event {
+            inspector::record_ws_received(result);
+        }
+
+        match event {
+            Event::Chat(event)
+                if account.reauth_dialog.is_some() && event.is_authenticated_request() =>
+            {
+                account.paused_chat_events.push(event);
+
+                Task::none()
+            }
+            Event::Chat(event) => {
+                let user_data = account.session.user_data.clone();
+
+                chat.update(&mut account.chat, user_data, event)
+            }
+
+            Event::FromWs(Ok(ws::controller::Event::Ready(sender))) => {
+                account.chat.ws_sender = sender;
+
+                self.account_update(account_id, Event::Chat(chat::Event::Reconnect))
+            }
+            Event::FromWs(Ok(event)) => {
+                let user_data = account.session.user_data.clone();
+
+                chat.ws_update(&mut account.chat, user_data, event)
+            }
+            // Lost connections are retried and shown in the chat instead of a popup
+            Event::FromWs(Err(
+                ws::controller::Error::Connection | ws::controller::Error::Disconnected,
+            )) => self.account_update(account_id, Event::Chat(chat::Event::ConnectionFailed)),
+            Event::FromWs(Err(ws::controller::Error::Send)) => {
+                self.account_update(account_id, Event::Chat(chat::Event::SendFailed))
+            }
+            Event::FromWs(Err(error)) => self.error_popup.clone().ws_update(error),
+
+            Event::TokenExpired(retry_event) => {
+                account.paused_chat_events.push(retry_event);
+
+                if account.reauth_dialog.is_none() {
+                    account.reauth_dialog = Some(reauth_dialog::State::new(
+                        account.chat.server.clone(),
+                        account.session.user_data.user_id,
+                        account.session.username.clone(),
+                    ));
+                }
+
+                Task::none()
+            }
+
+            Event::ReauthDialog(event) => match account.reauth_dialog.as_mut() {
+                Some(state) => reauth_dialog.update(state, event),
+                None => Task::none(),
+            },
+
+            Event::Reauthenticated(user_data) => {
+                account.session.user_data = user_data;
+                account.reauth_dialog = None;
+
+                session::save(&account.session);
+
+                // Reconnect first so that the websocket picks up the new token
+                let paused_chat_events: Vec<chat::Event> =
+                    std::mem::take(&mut account.paused_chat_events)
+                        .into_iter()
+                        .filter(|event| {
+                            !matches!(
+                                event,
+                                chat::Event::Reconnect | chat::Event::RetryConnection(_)
+                            )
+                        })
+                        .collect();
+
+                let reconnect_task =
+                    self.account_update(account_id, Event::Chat(chat::Event::Reconnect));
+                let replay_tasks: Vec<Task<Event>> = paused_chat_events
+                    .into_iter()
+                    .map(|event| self.account_update(account_id, Event::Chat(event)))
+                    .collect();
+
+                reconnect_task.chain(Task::batch(replay_tasks))
+            }
+
+            Event::Logout => {
+                let disconnect_event = ws::controller::SendEvent::Disconnect;
+                inspector::record_ws_sent(&disconnect_event);
+
+                if let Err(error) = account.chat.ws_sender.send(disconnect_event) {
+                    tracing::error!("Send error {error}");
+                }
+
+                // The outbox is kept on disk per account and flushed on the next login,
+                // so queued messages aren't silently lost
+                session::forget(&account.session);
+
+                self.accounts.retain(|account| account.id != account_id);
+                self.state.error_popup = error_popup::State::default();
+
+                match self.auth_state {
+                    AuthState::Authenticated(active_account_id)
+                    | AuthState::AddingAccount(active_account_id)
+                        if active_account_id == account_id =>
+                    {
+                        match self.accounts.first() {
+                            Some(account) => {
+                                self.auth_state = AuthState::Authenticated(account.id);
+                            }
+                            None => {
+                                self.state.login_form = login_form::State::default();
+                                self.auth_state = AuthState::Unauthenticated;
+                            }
+                        }
+                    }
+                    _ => (),
+                }
+
+                Task::none()
+            }
+
+            event => {
+                tracing::error!("Unexpected account event {:?}", event);
+
+                error_popup::ErrorEvent::String("Unexpected event".to_string()).task()
+            }
+        }
+    }
+}
@@ -1,22 +1,48 @@
+mod account;
 mod parts;
 mod theme;
 mod view;
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
-use iced::{Subscription, Task};
-use nultr_client_lib::{config, ws};
+use account::Account;
+use iced::{Subscription, Task, keyboard, window};
+use nultr_client_lib::ws;
 use nultr_shared_lib::request::AuthUserData;
-use parts::{chat, error_popup, login_form};
-use rust_api_kit::http::client::HttpClient;
+use parts::{chat, error_popup, inspector, login_form, reauth_dialog, settings};
+
+use crate::{
+    config, logging,
+    outbox::Outbox,
+    server::Server,
+    session::{self, Session},
+};
+
+pub type AccountId = usize;
 
 #[derive(Debug, Clone)]
 pub enum Event {
     LoginForm(login_form::Event),
     Chat(chat::Event),
     ErrorPopup(error_popup::Event),
+    ReauthDialog(reauth_dialog::Event),
+    Settings(settings::Event),
+    Inspector(inspector::Event),
     FromWs(Result<ws::controller::Event, ws::controller::Error>),
-    Authenticated(AuthUserData),
+    Authenticated(Session),
+    TokenExpired(chat::Event),
+    Reauthenticated(AuthUserData),
+    SwitchAccount(AccountId),
+    AddAccount,
+    CancelAddAccount,
+    OpenSettings,
+    CloseSettings,
+    SettingsSaved(Vec<String>),
+    ToggleInspector,
+    WindowFocusChanged(bool),
+    Tick,
+    Logout,
+    ForAccount(AccountId, Box<Event>),
 }
 
 trait WidgetErrorEvent {
@@ -28,6 +54,18 @@ impl Event {
     pub fn task(self) -> Task<Event> {
         Task::perform(async { self }, |value| value)
     }
+
+    fn is_account_scoped(&self) -> bool {
+        matches!(
+            self,
+            Event::Chat(_)
+                | Event::ReauthDialog(_)
+                | Event::FromWs(_)
+                | Event::TokenExpired(_)
+                | Event::Reauthenticated(_)
+                | Event::Logout
+        )
+    }
 }
 
 pub struct Ui {
@@ -35,53 +73,63 @@ pub struct Ui {
     chat: Arc<chat::Widget>,
     login: Arc<login_form::Widget>,
     error_popup: Arc<error_popup::Widget>,
+    reauth_dialog: Arc<reauth_dialog::Widget>,
+    settings: Arc<settings::Widget>,
+    inspector: Arc<inspector::Widget>,
     state: State,
+    accounts: Vec<Account>,
+    next_account_id: AccountId,
     auth_state: AuthState,
 }
 
 struct State {
-    chat: chat::State,
     login_form: login_form::State,
     error_popup: error_popup::State,
+    settings: Option<settings::State>,
+    inspector: Option<inspector::State>,
 }
 
 impl Default for State {
     fn default() -> Self {
-        let chat = chat::State::default();
         let login_form = login_form::State::default();
         let error_popup = error_popup::State::default();
 
         Self {
-            chat,
             login_form,
             error_popup,
+            settings: None,
+            inspector: None,
         }
     }
 }
 
 #[derive(Clone, Debug)]
 enum AuthState {
-    Authenticated(AuthUserData),
+    Authenticated(AccountId),
+    AddingAccount(AccountId),
     Unauthenticated,
 }
 
 impl Default for Ui {
     fn default() -> Self {
-        let theme = theme::Collection::default();
-        let base_url = config::get_variables().http_url.clone();
-        let http_client = Arc::new(HttpClient::new(base_url));
+        let theme = theme::Collection::new(config::get_config().theme);
 
-        let chat = Arc::new(chat::Widget {
-            theme: theme.chat,
-            http_client: http_client.clone(),
-        });
+        let chat = Arc::new(chat::Widget { theme: theme.chat });
         let login = Arc::new(login_form::Widget {
             theme: theme.login_form,
-            http_client,
+        });
+        let reauth_dialog = Arc::new(reauth_dialog::Widget {
+            theme: theme.reauth_dialog,
         });
         let error = Arc::new(error_popup::Widget {
             theme: theme.error_popup,
         });
+        let settings = Arc::new(settings::Widget {
+            theme: theme.settings,
+        });
+        let inspector = Arc::new(inspector::Widget {
+            theme: theme.inspector,
+        });
 
         let auth_state = AuthState::Unauthenticated;
 
@@ -92,13 +140,30 @@ impl Default for Ui {
             theme: theme.app,
             chat,
             login,
+            reauth_dialog,
+            settings,
+            inspector,
             state,
+            accounts: Vec::new(),
+            next_account_id: 0,
             error_popup: error,
         }
     }
 }
 
 impl Ui {
+    pub fn new() -> (Self, Task<Event>) {
+        let ui = Self::default();
+
+        let task = Task::batch(
+            session::load()
+                .into_iter()
+                .map(|session| login_form::Event::RestoreSession(session).task()),
+        );
+
+        (ui, task)
+    }
+
     pub fn update(&mut self, event: Event) -> Task<Event> {
         match (self.auth_state.clone(), event) {
             (_, Event::ErrorPopup(event)) => self
@@ -106,36 +171,161 @@ impl Ui {
                 .clone()
                 .update(&mut self.state.error_popup, event),
 
-            (_, Event::FromWs(Ok(ws::controller::Event::Ready(sender)))) => {
-                self.state.chat.ws_sender = sender;
+            (_, Event::LoginForm(event)) => {
+                self.login.clone().update(&mut self.state.login_form, event)
+            }
+
+            (_, Event::ForAccount(account_id, event)) => {
+                let task = self.account_update(account_id, *event);
+
+                Self::scope_task(task, account_id)
+            }
+            (AuthState::Authenticated(account_id), event) if event.is_account_scoped() => {
+                let task = self.account_update(account_id, event);
+
+                Self::scope_task(task, account_id)
+            }
+
+            (_, Event::Authenticated(session)) => {
+                let existing_account_id = self
+                    .accounts
+                    .iter()
+                    .find(|account| account.session.is_same_account(&session))
+                    .map(|account| account.id);
+
+                let (account_id, task) = match existing_account_id {
+                    Some(account_id) => {
+                        let task = self
+                            .account_update(account_id, Event::Reauthenticated(session.user_data));
+
+                        (account_id, Self::scope_task(task, account_id))
+                    }
+                    None => {
+                        let Some(server) = Server::from_profile_name(&session.profile_name) else {
+                            return error_popup::ErrorEvent::String(format!(
+                                "Unknown server profile: {}",
+                                session.profile_name
+                            ))
+                            .task();
+                        };
+
+                        let account_id = self.next_account_id;
+                        self.next_account_id += 1;
+
+                        let mut chat_state = chat::State::new(server, Outbox::load(&session));
+
+                        if self.accounts.is_empty() {
+                            chat_state.room_to_open = config::get_config().open_room_id;
+                        }
+
+                        // Connection is opened once the account subscription reports ready
+                        self.accounts
+                            .push(Account::new(account_id, session, chat_state));
+
+                        (account_id, Task::none())
+                    }
+                };
+
+                // Only the first restored or freshly logged in account takes focus
+                match self.auth_state {
+                    AuthState::Authenticated(_) => task,
+                    _ => task.chain(Event::SwitchAccount(account_id).task()),
+                }
+            }
+
+            (_, Event::SwitchAccount(account_id)) => {
+                if self.get_account_mut(account_id).is_none() {
+                    return Task::none();
+                }
+
+                let deselect_task = match self.auth_state {
+                    AuthState::Authenticated(active_account_id)
+                    | AuthState::AddingAccount(active_account_id)
+                        if active_account_id != account_id =>
+                    {
+                        let task = self.account_update(
+                            active_account_id,
+                            Event::Chat(chat::Event::DeselectRoom),
+                        );
+
+                        Self::scope_task(task, active_account_id)
+                    }
+                    _ => Task::none(),
+                };
+
+                self.auth_state = AuthState::Authenticated(account_id);
+
+                deselect_task
+            }
+
+            (AuthState::Authenticated(account_id), Event::AddAccount) => {
+                self.auth_state = AuthState::AddingAccount(account_id);
 
                 Task::none()
             }
 
-            (AuthState::Unauthenticated, Event::LoginForm(event)) => {
-                self.login.clone().update(&mut self.state.login_form, event)
+            (AuthState::AddingAccount(account_id), Event::CancelAddAccount) => {
+                self.auth_state = AuthState::Authenticated(account_id);
+
+                Task::none()
+            }
+
+            (AuthState::Authenticated(_), Event::OpenSettings) => {
+                self.state.settings = Some(settings::State::new(&config::get_config()));
+
+                Task::none()
+            }
+            (_, Event::Settings(event)) => match self.state.settings.as_mut() {
+                Some(settings_state) => self.settings.clone().update(settings_state, event),
+                None => Task::none(),
+            },
+            (_, Event::CloseSettings) => {
+                self.state.settings = None;
+
+                Task::none()
             }
-            (AuthState::Authenticated(user_data), Event::Chat(event)) => {
-                self.chat
-                    .clone()
-                    .update(&mut self.state.chat, user_data, event)
+            (_, Event::SettingsSaved(changed_profile_names)) => {
+                self.apply_settings(changed_profile_names)
             }
 
-            (AuthState::Authenticated(_), Event::FromWs(result)) => match result {
-                Ok(event) => self.chat.clone().ws_update(&mut self.state.chat, event),
-                Err(error) => self.error_popup.clone().ws_update(error),
+            (_, Event::ToggleInspector) => {
+                self.state.inspector = match self.state.inspector {
+                    Some(_) => None,
+                    None => Some(inspector::State::default()),
+                };
+
+                Task::none()
+            }
+            (_, Event::Inspector(event)) => match self.state.inspector.as_mut() {
+                Some(inspector_state) => self.inspector.clone().update(inspector_state, event),
+                None => Task::none(),
             },
-            (_, Event::Authenticated(user_data)) => {
-                self.auth_state = AuthState::Authenticated(user_data);
 
-                chat::Event::Reconnect.task()
+            // Every account keeps its own flag, so switching accounts needs no extra bookkeeping
+            (_, Event::WindowFocusChanged(is_focused)) => {
+                let account_ids: Vec<AccountId> =
+                    self.accounts.iter().map(|account| account.id).collect();
+
+                Task::batch(account_ids.into_iter().map(|account_id| {
+                    let task = self.account_update(
+                        account_id,
+                        Event::Chat(chat::Event::WindowFocusChanged(is_focused)),
+                    );
+
+                    Self::scope_task(task, account_id)
+                }))
             }
 
-            (AuthState::Authenticated(_), Event::LoginForm(_)) => {
-                error_popup::ErrorEvent::String("Already authorized".to_string()).task()
+            // Redraws countdowns and picks up new inspector entries
+            (_, Event::Tick) => {
+                if let Some(inspector_state) = self.state.inspector.as_mut() {
+                    inspector_state.refresh();
+                }
+
+                Task::none()
             }
 
-            (AuthState::Unauthenticated, event) => {
+            (_, event) => {
                 tracing::error!("{:?}", event);
 
                 error_popup::ErrorEvent::String("Unauthorized".to_string()).task()
@@ -144,6 +334,121 @@ impl Ui {
     }
 
     pub fn subscription(&self) -> Subscription<Event> {
-        Subscription::run(ws::controller::iced_integration::subscription).map(Event::FromWs)
+        let ws_subscriptions = self.accounts.iter().map(|account| {
+            Subscription::run_with_id(account.id, ws::controller::iced_integration::subscription())
+                .with(account.id)
+                .map(|(account_id, result)| {
+                    Event::ForAccount(account_id, Box::new(Event::FromWs(result)))
+                })
+        });
+
+        let tick_subscription = if self.state.inspector.is_some()
+            || self
+                .accounts
+                .iter()
+                .any(|account| account.chat.is_reconnecting())
+        {
+            iced::time::every(Duration::from_secs(1)).map(|_| Event::Tick)
+        } else {
+            Subscription::none()
+        };
+
+        let health_subscriptions = self
+            .accounts
+            .iter()
+            .filter(|account| account.chat.is_connected())
+            .map(|account| {
+                iced::time::every(chat::HEALTH_CHECK_INTERVAL)
+                    .with(account.id)
+                    .map(|(account_id, _)| {
+                        Event::ForAccount(
+                            account_id,
+                            Box::new(Event::Chat(chat::Event::CheckHealth)),
+                        )
+                    })
+            });
+
+        let inspector_subscription = keyboard::on_key_press(|key, _| match key {
+            keyboard::Key::Named(keyboard::key::Named::F12) => Some(Event::ToggleInspector),
+            _ => None,
+        });
+
+        let window_focus_subscription = iced::event::listen_with(|event, _, _| match event {
+            iced::Event::Window(window::Event::Focused) => Some(Event::WindowFocusChanged(true)),
+            iced::Event::Window(window::Event::Unfocused) => Some(Event::WindowFocusChanged(false)),
+            _ => None,
+        });
+
+        Subscription::batch(ws_subscriptions.chain(health_subscriptions).chain([
+            tick_subscription,
+            inspector_subscription,
+            window_focus_subscription,
+        ]))
+    }
+
+    // Font size is the only setting that needs a restart
+    fn apply_settings(&mut self, changed_profile_names: Vec<String>) -> Task<Event> {
+        let config = config::get_config();
+
+        logging::set_level(config.log_level);
+        self.apply_theme(theme::Collection::new(config.theme));
+        self.state.login_form.refresh_server();
+
+        let reconnect_tasks: Vec<Task<Event>> = self
+            .accounts
+            .iter_mut()
+            .filter(|account| changed_profile_names.contains(&account.session.profile_name))
+            .filter_map(|account| {
+                let server = Server::from_profile_name(&account.session.profile_name)?;
+
+                if let Some(reauth_dialog_state) = account.reauth_dialog.as_mut() {
+                    reauth_dialog_state.set_server(server.clone());
+                }
+
+                account.chat.server = server;
+
+                Some(account.id)
+            })
+            .collect::<Vec<AccountId>>()
+            .into_iter()
+            .map(|account_id| {
+                let task = self.account_update(account_id, Event::Chat(chat::Event::Reconnect));
+
+                Self::scope_task(task, account_id)
+            })
+            .collect();
+
+        Task::batch(reconnect_tasks)
+    }
+
+    fn apply_theme(&mut self, theme: theme::Collection) {
+        self.theme = theme.app;
+        self.chat = Arc::new(chat::Widget { theme: theme.chat });
+        self.login = Arc::new(login_form::Widget {
+            theme: theme.login_form,
+        });
+        self.reauth_dialog = Arc::new(reauth_dialog::Widget {
+            theme: theme.reauth_dialog,
+        });
+        self.error_popup = Arc::new(error_popup::Widget {
+            theme: theme.error_popup,
+        });
+        self.settings = Arc::new(settings::Widget {
+            theme: theme.settings,
+        });
+        self.inspector = Arc::new(inspector::Widget {
+            theme: theme.inspector,
+        });
+    }
+
+    // Tasks spawned by an account keep talking to that account, even after a switch
+    fn scope_task(task: Task<Event>, account_id: AccountId) -> Task<Event> {
+        task.map(move |event| {
+            if event.is_account_scoped() {
+                Event::ForAccount(account_id, Box::new(event))
+            } else {
+                event
+            }
+        })
     }
 }
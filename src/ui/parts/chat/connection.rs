@@ -0,0 +1,85 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use iced::Task;
+use nultr_shared_lib::{
+    request::{AuthUserData, AuthenticatedUnexpectedErrorResponse, GetUsersRequest},
+    util::MonoResult,
+};
+use rust_api_kit::http::client::{AuthenticatedHttpClientTrait, UnexpectedHttpError};
+
+use crate::{
+    inspector,
+    server::Server,
+    ui::{self, WidgetErrorEvent},
+};
+
+use super::{ConnectionState, Event, State, Widget};
+
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+const STABLE_CONNECTION_PERIOD: Duration = Duration::from_secs(30);
+
+impl Widget {
+    pub(super) fn schedule_reconnect(state: &mut State) -> Task<ui::Event> {
+        if let ConnectionState::Reconnecting { .. } = state.connection_state {
+            return Task::none();
+        }
+
+        // Only a connection that held for a while earns a fresh backoff
+        if let Some(connected_at) = state.connected_at.take()
+            && connected_at.elapsed() >= STABLE_CONNECTION_PERIOD
+        {
+            state.reconnect_attempt = 0;
+        }
+
+        let delay = get_reconnect_delay(state.reconnect_attempt);
+
+        state.reconnect_attempt = state.reconnect_attempt.saturating_add(1);
+        state.reconnect_generation += 1;
+        state.connection_state = ConnectionState::Reconnecting {
+            retry_at: Instant::now() + delay,
+        };
+
+        let generation = state.reconnect_generation;
+
+        Task::perform(tokio::time::sleep(delay), move |_| {
+            Event::RetryConnection(generation).event()
+        })
+    }
+
+    // Checking the token over http first keeps an expired session from retrying forever
+    pub(super) async fn check_connection(
+        self: Arc<Self>,
+        server: Arc<Server>,
+        user_data: AuthUserData,
+    ) -> MonoResult<ui::Event> {
+        let request = GetUsersRequest {};
+
+        inspector::inspect_http(request, |request| {
+            server.http_client.request(request, user_data.token.clone())
+        })
+        .await
+        .map_err(|error| match error {
+            UnexpectedHttpError::Api(AuthenticatedUnexpectedErrorResponse::InvalidToken) => {
+                ui::Event::TokenExpired(Event::Reconnect)
+            }
+            _ => Event::ConnectionFailed.event(),
+        })?;
+
+        Ok(Event::Reconnect.event())
+    }
+}
+
+fn get_reconnect_delay(attempt: u32) -> Duration {
+    let delay = INITIAL_RECONNECT_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(MAX_RECONNECT_DELAY);
+
+    // Random jitter within the upper half keeps clients from reconnecting in lockstep
+    let delay_millis = delay.as_millis() as u64;
+
+    Duration::from_millis(rand::random_range(delay_millis / 2..=delay_millis))
+}
@@ -0,0 +1,140 @@
+use std::time::{Duration, Instant};
+
+use iced::Task;
+use nultr_client_lib::ws;
+use nultr_shared_lib::request::{UuidIdentifier, WsMessageRequest};
+
+use crate::ui::{self, WidgetErrorEvent};
+
+use super::{
+    ChatAreaState, ChatMessage, ConnectionState, Event, OutgoingChatMessage, OutgoingMessageState,
+    State, Widget,
+};
+
+const ACK_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl Widget {
+    pub(super) fn retry_message(state: &mut State, uuid: UuidIdentifier) -> Task<ui::Event> {
+        let Some(message) = state
+            .outbox
+            .get_messages()
+            .iter()
+            .find(|message| message.uuid == uuid)
+        else {
+            return Task::none();
+        };
+
+        let request = WsMessageRequest::from(message);
+
+        state.failed_message_uuids.remove(&uuid);
+        Self::set_outgoing_message_state(state, uuid, OutgoingMessageState::Created);
+
+        // Offline retries stay queued until the outbox is flushed
+        match state.connection_state {
+            ConnectionState::Connected => {
+                let controller_event = ws::controller::SendEvent::Message(request);
+
+                Event::SendToWs(controller_event)
+                    .task()
+                    .chain(Self::track_sent_message(state, uuid))
+            }
+            _ => Task::none(),
+        }
+    }
+
+    pub(super) fn delete_message(state: &mut State, uuid: UuidIdentifier) -> Task<ui::Event> {
+        Self::forget_pending_messages(state, &[uuid]);
+
+        if let ChatAreaState::RoomSelected(chat_area_state) = &mut state.chat_area_state {
+            chat_area_state
+                .messages
+                .retain(|message| Self::get_message_uuid(message) != uuid);
+        }
+
+        Task::none()
+    }
+
+    // Starts the acknowledgement timer for a message handed to the websocket
+    pub(super) fn track_sent_message(state: &mut State, uuid: UuidIdentifier) -> Task<ui::Event> {
+        state.awaiting_ack.insert(uuid, Instant::now());
+        state.unsent_message_uuids.insert(uuid);
+
+        Task::perform(tokio::time::sleep(ACK_TIMEOUT), move |_| {
+            Event::AckTimedOut(uuid).event()
+        })
+    }
+
+    pub(super) fn check_ack_deadline(state: &mut State, uuid: UuidIdentifier) -> Task<ui::Event> {
+        // A retry resets the send time, so timers from earlier attempts are ignored
+        let is_expired = state
+            .awaiting_ack
+            .get(&uuid)
+            .is_some_and(|sent_at| sent_at.elapsed() >= ACK_TIMEOUT);
+
+        if is_expired {
+            Self::mark_messages_failed(state, vec![uuid]);
+        }
+
+        Task::none()
+    }
+
+    // The send error does not say which message it was. Messages already written to the
+    // socket may still be acknowledged, so only the ones never written are failed here
+    pub(super) fn handle_send_failure(state: &mut State) -> Task<ui::Event> {
+        let message_uuids = state.unsent_message_uuids.drain().collect();
+
+        Self::mark_messages_failed(state, message_uuids);
+
+        Task::none()
+    }
+
+    pub(super) fn forget_pending_messages(state: &mut State, message_uuids: &[UuidIdentifier]) {
+        state.outbox.remove(message_uuids);
+
+        for uuid in message_uuids {
+            state.awaiting_ack.remove(uuid);
+            state.unsent_message_uuids.remove(uuid);
+            state.failed_message_uuids.remove(uuid);
+        }
+    }
+
+    fn mark_messages_failed(state: &mut State, message_uuids: Vec<UuidIdentifier>) {
+        for uuid in message_uuids {
+            state.awaiting_ack.remove(&uuid);
+            state.unsent_message_uuids.remove(&uuid);
+            state.failed_message_uuids.insert(uuid);
+
+            Self::set_outgoing_message_state(state, uuid, OutgoingMessageState::Failed);
+        }
+    }
+
+    // Unlike change_outgoing_messages_state this may move a message back, as retries do
+    pub(super) fn set_outgoing_message_state(
+        state: &mut State,
+        uuid: UuidIdentifier,
+        message_state: OutgoingMessageState,
+    ) {
+        if let ChatAreaState::RoomSelected(chat_area_state) = &mut state.chat_area_state
+            && let Some(message) =
+                chat_area_state
+                    .messages
+                    .iter_mut()
+                    .find_map(|message| match message {
+                        ChatMessage::Outgoing(message) if message.uuid == uuid => Some(message),
+                        _ => None,
+                    })
+            && Self::is_awaiting_ack(message)
+        {
+            message.set_state(message_state);
+        }
+    }
+
+    fn is_awaiting_ack(message: &OutgoingChatMessage) -> bool {
+        matches!(
+            message.state,
+            OutgoingMessageState::Created
+                | OutgoingMessageState::Sent
+                | OutgoingMessageState::Failed
+        )
+    }
+}
@@ -0,0 +1,111 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use nultr_shared_lib::{
+    request::{AuthUserData, GetUsersRequest, UuidIdentifier},
+    util::MonoResult,
+};
+use rust_api_kit::http::client::{AuthenticatedHttpClientTrait, UnexpectedHttpError};
+
+use crate::{
+    inspector,
+    server::Server,
+    ui::{self, WidgetErrorEvent},
+};
+
+use super::{ConnectionState, Event, State, Widget};
+
+pub const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const DEGRADED_LATENCY: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthStatus {
+    Connected,
+    Connecting,
+    Degraded,
+    Offline,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Health {
+    pub ws_rtt: Option<Duration>,
+    pub http_latency: Option<Duration>,
+    pub is_http_failing: bool,
+    pub last_frame_at: Option<Instant>,
+}
+
+impl State {
+    pub fn is_connected(&self) -> bool {
+        matches!(self.connection_state, ConnectionState::Connected)
+    }
+
+    pub fn get_health_status(&self) -> HealthStatus {
+        match self.connection_state {
+            ConnectionState::Connected => {
+                let is_slow = [self.health.ws_rtt, self.health.http_latency]
+                    .into_iter()
+                    .flatten()
+                    .any(|latency| latency >= DEGRADED_LATENCY);
+
+                if is_slow || self.health.is_http_failing {
+                    HealthStatus::Degraded
+                } else {
+                    HealthStatus::Connected
+                }
+            }
+            ConnectionState::Connecting | ConnectionState::Reconnecting { .. } => {
+                HealthStatus::Connecting
+            }
+            ConnectionState::Disconnected => HealthStatus::Offline,
+        }
+    }
+}
+
+impl Widget {
+    pub(super) fn record_frame(state: &mut State) {
+        state.health.last_frame_at = Some(Instant::now());
+    }
+
+    // Message acknowledgements double as websocket round trip samples
+    pub(super) fn record_ws_rtt(state: &mut State, uuid: UuidIdentifier) {
+        if let Some(sent_at) = state.awaiting_ack.get(&uuid) {
+            state.health.ws_rtt = Some(sent_at.elapsed());
+        }
+    }
+
+    pub(super) fn record_http_latency(state: &mut State, latency: Option<Duration>) {
+        state.health.is_http_failing = latency.is_none();
+
+        if latency.is_some() {
+            state.health.http_latency = latency;
+        }
+    }
+
+    // Any answer from the server counts, only transport failures mark http as failing
+    pub(super) async fn check_health(
+        self: Arc<Self>,
+        server: Arc<Server>,
+        user_data: AuthUserData,
+    ) -> MonoResult<ui::Event> {
+        let request = GetUsersRequest {};
+        let started_at = Instant::now();
+
+        let result = inspector::inspect_http(request, |request| {
+            server.http_client.request(request, user_data.token.clone())
+        })
+        .await;
+
+        let latency = match result {
+            Ok(_) | Err(UnexpectedHttpError::Api(_)) => Some(started_at.elapsed()),
+            Err(error) => {
+                tracing::error!("Health check failed: {:?}", error);
+
+                None
+            }
+        };
+
+        Ok(Event::HealthChecked(latency).event())
+    }
+}
@@ -0,0 +1,591 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use chrono::NaiveDateTime;
+use iced::{
+    Task,
+    widget::{container, scrollable::Viewport},
+};
+use nultr_shared_lib::request::{AuthUserData, MessageResponse, UuidIdentifier};
+
+use crate::{
+    ui::{self, WidgetErrorEvent},
+    util::task_perform,
+};
+
+use super::{
+    ChatAreaRoomSelectedState, ChatAreaState, ChatMessage, Event, MessagesLoad,
+    OutgoingMessageState, OutgoingStateChange, State, Widget, error_popup,
+};
+
+pub(super) const MESSAGES_PAGE_SIZE: u64 = 20;
+
+// Distance from the top of the history at which the next page is requested
+const LOAD_OLDER_MESSAGES_THRESHOLD: f32 = 100.0;
+
+/// Position of a message in the history, ordered the same way the messages are
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MessagesCursor {
+    pub created_at: NaiveDateTime,
+    pub uuid: UuidIdentifier,
+}
+
+impl MessagesCursor {
+    pub(super) fn from_message(message: &ChatMessage) -> Self {
+        Self {
+            created_at: Widget::get_message_created_at(message),
+            uuid: Widget::get_message_uuid(message),
+        }
+    }
+
+    pub(super) fn from_response(message: &MessageResponse) -> Self {
+        Self {
+            created_at: message.created_at,
+            uuid: message.uuid,
+        }
+    }
+}
+
+/// Page to request instead of the estimated one after a page didn't line up with the cursor
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistorySeek {
+    /// The page was newer than the cursor, which happens after messages were missed
+    Older(u64),
+    /// The page was older than the cursor, so history in between was skipped
+    Newer(u64),
+}
+
+/// Which page a messages request asked for and what it was expected to contain
+#[derive(Clone, Copy, Debug)]
+pub struct PagePlan {
+    pub page: u64,
+    pub seek: Option<HistorySeek>,
+    pub expects_cursor: bool,
+}
+
+impl Widget {
+    pub(super) fn load_messages_page(
+        self: Arc<Self>,
+        state: &mut State,
+        user_data: &AuthUserData,
+    ) -> Task<ui::Event> {
+        let ChatAreaState::RoomSelected(chat_area_state) = &mut state.chat_area_state else {
+            return error_popup::ErrorEvent::String(
+                "Cannot load messages: no user selected".to_string(),
+            )
+            .task();
+        };
+
+        let load = MessagesLoad {
+            room_id: chat_area_state.room_id,
+            generation: chat_area_state.load_generation,
+            plan: Self::get_page_plan(chat_area_state),
+        };
+
+        chat_area_state.loading_page = Some(load.plan.page);
+
+        let (task, handle) =
+            task_perform(self.load_messages(state.server.clone(), load, user_data.clone()))
+                .abortable();
+
+        state
+            .messages_load_handles
+            .insert(load.get_handle_key(), handle);
+
+        task.chain(Event::MessagesLoadFinished(load).task())
+    }
+
+    /// The messages scrollable is anchored to the bottom, so prepending a page
+    /// keeps the message that was on screen in place
+    pub(super) fn load_older_messages_on_scroll(
+        self: Arc<Self>,
+        state: &mut State,
+        user_data: &AuthUserData,
+        viewport: Viewport,
+    ) -> Task<ui::Event> {
+        let ChatAreaState::RoomSelected(chat_area_state) = &state.chat_area_state else {
+            return Task::none();
+        };
+
+        let is_near_top = viewport.absolute_offset_reversed().y <= LOAD_OLDER_MESSAGES_THRESHOLD;
+
+        if is_near_top
+            && chat_area_state.has_more_messages
+            && chat_area_state.loading_page.is_none()
+        {
+            self.load_messages_page(state, user_data)
+        } else {
+            Task::none()
+        }
+    }
+
+    pub(super) fn get_messages_area_id() -> container::Id {
+        container::Id::new("messages-area")
+    }
+
+    pub(super) fn get_messages_content_id() -> container::Id {
+        container::Id::new("messages-content")
+    }
+
+    /// A history shorter than the viewport can't be scrolled, so `MessagesScrolled`
+    /// never asks for the next page; the rendered heights are compared instead
+    pub(super) fn check_messages_fill_viewport(state: &State) -> Task<ui::Event> {
+        let ChatAreaState::RoomSelected(chat_area_state) = &state.chat_area_state else {
+            return Task::none();
+        };
+
+        if !chat_area_state.has_more_messages {
+            return Task::none();
+        }
+
+        let generation = chat_area_state.load_generation;
+
+        container::visible_bounds(Self::get_messages_area_id()).and_then(move |area| {
+            container::visible_bounds(Self::get_messages_content_id()).and_then(move |content| {
+                if content.height < area.height {
+                    Event::MessagesViewportUnfilled(generation).task()
+                } else {
+                    Task::none()
+                }
+            })
+        })
+    }
+
+    pub(super) fn load_messages_to_fill_viewport(
+        self: Arc<Self>,
+        state: &mut State,
+        user_data: &AuthUserData,
+        generation: u64,
+    ) -> Task<ui::Event> {
+        let ChatAreaState::RoomSelected(chat_area_state) = &state.chat_area_state else {
+            return Task::none();
+        };
+
+        if chat_area_state.load_generation == generation
+            && chat_area_state.has_more_messages
+            && chat_area_state.loading_page.is_none()
+        {
+            self.load_messages_page(state, user_data)
+        } else {
+            Task::none()
+        }
+    }
+
+    // Runs after the request whatever its outcome, so a failed page can be retried by scrolling
+    pub(super) fn finish_messages_load(state: &mut State, load: MessagesLoad) -> Task<ui::Event> {
+        state.messages_load_handles.remove(&load.get_handle_key());
+
+        if let ChatAreaState::RoomSelected(chat_area_state) = &mut state.chat_area_state
+            && load.is_current(chat_area_state)
+            && chat_area_state.loading_page == Some(load.plan.page)
+        {
+            chat_area_state.loading_page = None;
+        }
+
+        Task::none()
+    }
+
+    /// Merges a loaded page and returns whether another one has to be loaded
+    /// right away because this one didn't line up with the cursor
+    pub(super) fn add_messages_page(
+        chat_area_state: &mut ChatAreaRoomSelectedState,
+        load: MessagesLoad,
+        messages: Vec<ChatMessage>,
+    ) -> bool {
+        let is_full_page = messages.len() as u64 >= MESSAGES_PAGE_SIZE;
+        let oldest = messages.iter().map(MessagesCursor::from_message).min();
+        let newest = messages.iter().map(MessagesCursor::from_message).max();
+
+        chat_area_state.history_seek = match (chat_area_state.history_cursor, oldest, newest) {
+            (Some(cursor), Some(oldest), Some(newest)) => {
+                Self::get_history_seek(load, cursor, oldest, newest, is_full_page)
+            }
+            _ => None,
+        };
+
+        Self::merge_messages(chat_area_state, messages);
+
+        let is_seeking = chat_area_state.history_seek.is_some();
+        chat_area_state.has_more_messages = is_seeking || is_full_page;
+
+        is_seeking
+    }
+
+    /// Keeps the history ordered by creation time with every uuid present once,
+    /// server copies replace whatever was there before but never move its state back
+    pub(super) fn merge_messages(
+        chat_area_state: &mut ChatAreaRoomSelectedState,
+        mut messages: Vec<ChatMessage>,
+    ) {
+        let message_uuids: HashSet<UuidIdentifier> =
+            messages.iter().map(Self::get_message_uuid).collect();
+
+        let mut replaced_states: HashMap<
+            UuidIdentifier,
+            (OutgoingMessageState, Vec<OutgoingStateChange>),
+        > = HashMap::new();
+
+        chat_area_state.messages.retain_mut(|message| {
+            let uuid = Self::get_message_uuid(message);
+            let is_replaced = message_uuids.contains(&uuid);
+
+            if is_replaced && let ChatMessage::Outgoing(message) = message {
+                replaced_states.insert(
+                    uuid,
+                    (
+                        message.state.clone(),
+                        std::mem::take(&mut message.state_history),
+                    ),
+                );
+            }
+
+            !is_replaced
+        });
+
+        for message in messages.iter_mut() {
+            if let ChatMessage::Outgoing(message) = message
+                && let Some((replaced_state, mut state_history)) =
+                    replaced_states.remove(&message.uuid)
+            {
+                // A read receipt can arrive before the page that still says received
+                if replaced_state.is_upgraded_by(&message.state) {
+                    // The server doesn't say when its state was reached
+                    state_history.push(OutgoingStateChange {
+                        state: message.state.clone(),
+                        changed_at: None,
+                    });
+                } else {
+                    message.state = replaced_state;
+                }
+
+                message.state_history = state_history;
+            }
+        }
+
+        chat_area_state.messages.extend(messages);
+        chat_area_state
+            .messages
+            .sort_by_key(MessagesCursor::from_message);
+
+        chat_area_state.history_cursor = chat_area_state
+            .messages
+            .iter()
+            .find(|message| Self::is_stored_message(message))
+            .map(MessagesCursor::from_message);
+    }
+
+    // This is offset pagination with a correction step, not cursor pagination: GetMessagesRequest
+    // only takes a page counted from the newest message. The page holding the message right
+    // before the oldest loaded one is estimated from how many stored messages are loaded, and
+    // get_history_seek moves to a neighbouring page when messages that arrived in the meantime
+    // shifted it. A `before` field upstream would make both unnecessary
+    fn get_page_plan(chat_area_state: &ChatAreaRoomSelectedState) -> PagePlan {
+        if let Some(seek) = chat_area_state.history_seek {
+            let page = match seek {
+                HistorySeek::Older(page) | HistorySeek::Newer(page) => page,
+            };
+
+            return PagePlan {
+                page,
+                seek: Some(seek),
+                expects_cursor: matches!(seek, HistorySeek::Newer(_)),
+            };
+        }
+
+        let stored_messages_count = chat_area_state
+            .messages
+            .iter()
+            .filter(|message| Self::is_stored_message(message))
+            .count() as u64;
+
+        PagePlan {
+            page: stored_messages_count / MESSAGES_PAGE_SIZE,
+            seek: None,
+            expects_cursor: stored_messages_count % MESSAGES_PAGE_SIZE != 0,
+        }
+    }
+
+    fn get_history_seek(
+        load: MessagesLoad,
+        cursor: MessagesCursor,
+        oldest: MessagesCursor,
+        newest: MessagesCursor,
+        is_full_page: bool,
+    ) -> Option<HistorySeek> {
+        let plan = load.plan;
+
+        if newest < cursor {
+            // Without an expected overlap the page simply follows the cursor
+            if !plan.expects_cursor || plan.page == 0 {
+                return None;
+            }
+
+            tracing::debug!(
+                "Page {} of room {} ends before message {}, loading page {}",
+                plan.page,
+                load.room_id,
+                cursor.uuid,
+                plan.page - 1
+            );
+
+            Some(HistorySeek::Newer(plan.page - 1))
+        } else if oldest >= cursor && is_full_page {
+            // Seeking newer pages stops here, the older page after it was already merged
+            match plan.seek {
+                Some(HistorySeek::Newer(_)) => None,
+                _ => Some(HistorySeek::Older(plan.page + 1)),
+            }
+        } else {
+            None
+        }
+    }
+
+    fn is_stored_message(message: &ChatMessage) -> bool {
+        match message {
+            ChatMessage::Incoming(_) => true,
+            ChatMessage::Outgoing(message) => matches!(
+                message.state,
+                OutgoingMessageState::Received | OutgoingMessageState::Read
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, TimeDelta};
+    use nultr_shared_lib::request::Identifier;
+    use uuid::Uuid;
+
+    use super::super::{IncomingChatMessage, OutgoingChatMessage};
+    use super::*;
+
+    const ROOM_ID: Identifier = 1;
+
+    fn get_created_at(index: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 3, 12)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            + TimeDelta::seconds(i64::from(index))
+    }
+
+    fn get_incoming_message(index: u32) -> ChatMessage {
+        ChatMessage::Incoming(IncomingChatMessage {
+            user_id: 2,
+            uuid: Uuid::from_u128(u128::from(index)),
+            content: format!("message {index}"),
+            created_at: get_created_at(index),
+        })
+    }
+
+    fn get_outgoing_message(index: u32, state: OutgoingMessageState) -> ChatMessage {
+        ChatMessage::Outgoing(OutgoingChatMessage {
+            user_id: 1,
+            uuid: Uuid::from_u128(u128::from(index)),
+            content: format!("message {index}"),
+            created_at: get_created_at(index),
+            state_history: Widget::get_state_history(&state, get_created_at(index)),
+            state,
+        })
+    }
+
+    // Pages are counted from the newest of `total` messages, the way the server does it
+    fn get_server_page(total: u32, page: u64) -> Vec<ChatMessage> {
+        let page_size = MESSAGES_PAGE_SIZE as u32;
+        let end = total.saturating_sub(page as u32 * page_size);
+        let start = end.saturating_sub(page_size);
+
+        (start..end).map(get_incoming_message).collect()
+    }
+
+    fn get_chat_area_state(messages: Vec<ChatMessage>) -> ChatAreaRoomSelectedState {
+        let mut chat_area_state = ChatAreaRoomSelectedState {
+            room_id: ROOM_ID,
+            messages: Vec::new(),
+            history_cursor: None,
+            history_seek: None,
+            load_generation: 0,
+            loading_page: None,
+            has_more_messages: true,
+            unread_message_uuids: HashSet::new(),
+        };
+
+        Widget::merge_messages(&mut chat_area_state, messages);
+
+        chat_area_state
+    }
+
+    // Plans the next page and merges what the server returns for it
+    fn load_next_page(chat_area_state: &mut ChatAreaRoomSelectedState, total: u32) -> PagePlan {
+        let plan = Widget::get_page_plan(chat_area_state);
+        let load = MessagesLoad {
+            room_id: ROOM_ID,
+            generation: 0,
+            plan,
+        };
+
+        Widget::add_messages_page(chat_area_state, load, get_server_page(total, plan.page));
+
+        plan
+    }
+
+    fn get_uuids(chat_area_state: &ChatAreaRoomSelectedState) -> Vec<UuidIdentifier> {
+        chat_area_state
+            .messages
+            .iter()
+            .map(Widget::get_message_uuid)
+            .collect()
+    }
+
+    #[test]
+    fn messages_shifting_a_whole_page_seek_older() {
+        let mut chat_area_state = get_chat_area_state(Vec::new());
+
+        load_next_page(&mut chat_area_state, 40);
+
+        // A full page arrived on the server, so page 1 now holds what page 0 did
+        load_next_page(&mut chat_area_state, 60);
+
+        assert_eq!(chat_area_state.history_seek, Some(HistorySeek::Older(2)));
+
+        let plan = load_next_page(&mut chat_area_state, 60);
+
+        assert_eq!(plan.page, 2);
+        assert_eq!(chat_area_state.history_seek, None);
+        assert_eq!(
+            chat_area_state
+                .history_cursor
+                .map(|cursor| cursor.created_at),
+            Some(get_created_at(0))
+        );
+        assert_eq!(get_uuids(&chat_area_state).len(), 40);
+    }
+
+    #[test]
+    fn page_past_the_cursor_seeks_newer_and_stops() {
+        // One more stored message than the server has, so the estimate lands a page too far
+        let mut messages = get_server_page(40, 0);
+        messages.push(get_incoming_message(1000));
+
+        let mut chat_area_state = get_chat_area_state(messages);
+        let plan = load_next_page(&mut chat_area_state, 40);
+
+        assert_eq!(plan.page, 1);
+        assert_eq!(chat_area_state.history_seek, Some(HistorySeek::Newer(0)));
+        assert!(chat_area_state.has_more_messages);
+
+        let plan = load_next_page(&mut chat_area_state, 40);
+
+        assert_eq!(plan.seek, Some(HistorySeek::Newer(0)));
+        assert_eq!(chat_area_state.history_seek, None);
+        assert_eq!(get_uuids(&chat_area_state).len(), 41);
+    }
+
+    #[test]
+    fn short_last_page_ends_history() {
+        let mut chat_area_state = get_chat_area_state(Vec::new());
+
+        load_next_page(&mut chat_area_state, 25);
+
+        assert!(chat_area_state.has_more_messages);
+
+        load_next_page(&mut chat_area_state, 25);
+
+        assert_eq!(chat_area_state.history_seek, None);
+        assert!(!chat_area_state.has_more_messages);
+        assert_eq!(get_uuids(&chat_area_state).len(), 25);
+    }
+
+    #[test]
+    fn duplicate_uuid_is_replaced_once_and_kept_sorted() {
+        let mut chat_area_state = get_chat_area_state(vec![
+            get_incoming_message(3),
+            get_outgoing_message(2, OutgoingMessageState::Created),
+        ]);
+
+        // Same creation time as message 2, so only the uuid orders it
+        let same_time_message = ChatMessage::Incoming(IncomingChatMessage {
+            user_id: 2,
+            uuid: Uuid::from_u128(1),
+            content: "same time".to_string(),
+            created_at: get_created_at(2),
+        });
+
+        Widget::merge_messages(
+            &mut chat_area_state,
+            vec![
+                get_outgoing_message(2, OutgoingMessageState::Received),
+                same_time_message,
+                get_incoming_message(3),
+            ],
+        );
+
+        assert_eq!(
+            get_uuids(&chat_area_state),
+            vec![Uuid::from_u128(1), Uuid::from_u128(2), Uuid::from_u128(3)]
+        );
+
+        let cursors: Vec<MessagesCursor> = chat_area_state
+            .messages
+            .iter()
+            .map(MessagesCursor::from_message)
+            .collect();
+
+        assert!(cursors.is_sorted());
+
+        let ChatMessage::Outgoing(message) = &chat_area_state.messages[1] else {
+            panic!("The server copy of an outgoing message should stay outgoing");
+        };
+
+        assert_eq!(message.state, OutgoingMessageState::Received);
+        assert_eq!(
+            message
+                .state_history
+                .iter()
+                .map(|change| change.state.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                OutgoingMessageState::Created,
+                OutgoingMessageState::Received
+            ]
+        );
+        assert_eq!(message.state_history[1].changed_at, None);
+    }
+
+    fn get_merged_state(
+        local_state: OutgoingMessageState,
+        server_state: OutgoingMessageState,
+    ) -> OutgoingMessageState {
+        let mut chat_area_state = get_chat_area_state(vec![get_outgoing_message(0, local_state)]);
+
+        Widget::merge_messages(
+            &mut chat_area_state,
+            vec![get_outgoing_message(0, server_state)],
+        );
+
+        let ChatMessage::Outgoing(message) = &chat_area_state.messages[0] else {
+            panic!("The server copy of an outgoing message should stay outgoing");
+        };
+
+        message.state.clone()
+    }
+
+    #[test]
+    fn server_copy_never_moves_state_back() {
+        assert_eq!(
+            get_merged_state(OutgoingMessageState::Read, OutgoingMessageState::Received),
+            OutgoingMessageState::Read
+        );
+        assert_eq!(
+            get_merged_state(OutgoingMessageState::Sent, OutgoingMessageState::Received),
+            OutgoingMessageState::Received
+        );
+        assert_eq!(
+            get_merged_state(OutgoingMessageState::Failed, OutgoingMessageState::Read),
+            OutgoingMessageState::Read
+        );
+    }
+}
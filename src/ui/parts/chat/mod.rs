@@ -1,32 +1,50 @@
+mod connection;
+mod delivery;
+mod health;
+mod history;
+mod receipts;
+mod resync;
 mod view;
 
-use chrono::{NaiveDateTime, Utc};
-use iced::{Task, widget::scrollable};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use iced::{Task, task, widget::scrollable};
 use nultr_client_lib::{
-    config,
     util::create_stub_sender,
     ws::{self, controller::SendEvent},
 };
 use nultr_shared_lib::{
     request::{
-        AuthUserData, CreatePrivateRoomRequest, CreatePrivateRoomResponse, GetMessagesRequest,
-        GetMessagesResponse, GetRoomsRequest, GetRoomsResponse, GetUsersRequest, GetUsersResponse,
-        Identifier, MessageResponse, UuidIdentifier, WsMarkMessagesReadRequest, WsMessageRequest,
+        AuthUserData, AuthenticatedUnexpectedErrorResponse, CreatePrivateRoomRequest,
+        CreatePrivateRoomResponse, GetMessagesRequest, GetMessagesResponse, GetRoomsRequest,
+        GetRoomsResponse, GetUsersRequest, GetUsersResponse, Identifier, MessageResponse,
+        UuidIdentifier, WsMessageRequest,
     },
     util::MonoResult,
 };
-use rust_api_kit::http::client::{AuthenticatedHttpClientTrait, BearerToken, HttpClient};
-use std::sync::Arc;
+use rust_api_kit::http::client::{AuthenticatedHttpClientTrait, UnexpectedHttpError};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tokio::sync::mpsc;
 use uuid::Uuid;
 
 use crate::{
-    ui::{self, WidgetErrorEvent, theme},
+    inspector,
+    outbox::{Outbox, OutboxMessage},
+    server::Server,
+    ui::{self, AccountId, WidgetErrorEvent, theme},
     util::task_perform,
 };
 
 use super::error_popup;
 
+use health::Health;
+pub use health::{HEALTH_CHECK_INTERVAL, HealthStatus};
+use history::{HistorySeek, MESSAGES_PAGE_SIZE, MessagesCursor, PagePlan};
+use resync::MissedMessages;
+
 #[derive(Debug, Clone)]
 pub enum Event {
     InputChanged(String),
@@ -40,9 +58,45 @@ pub enum Event {
     AddCreatedRoom(CreatePrivateRoomResponse),
     AddRooms(GetRoomsResponse),
     AddUsers(GetUsersResponse),
-    AddMessages(GetMessagesResponse),
+    AddMessages(MessagesLoad, GetMessagesResponse),
+    FetchMissedMessages,
+    AddMissedMessages(MissedMessages),
+    MessagesLoadFinished(MessagesLoad),
+    MessagesScrolled(scrollable::Viewport),
+    MessagesViewportUnfilled(u64),
+    CheckVisibleMessages,
+    MessagesSeen(Identifier, Vec<UuidIdentifier>),
+    WindowFocusChanged(bool),
     SelectRoom(Identifier),
     SendToWs(ws::controller::SendEvent),
+    RetryConnection(u64),
+    RetryNow,
+    ConnectionFailed,
+    AckTimedOut(UuidIdentifier),
+    SendFailed,
+    RetryMessage(UuidIdentifier),
+    DeleteMessage(UuidIdentifier),
+    CheckHealth,
+    HealthChecked(Option<Duration>),
+    SwitchAccount(AccountId),
+    AddAccount,
+    OpenSettings,
+    Logout,
+}
+
+impl Event {
+    pub fn is_authenticated_request(&self) -> bool {
+        matches!(
+            self,
+            Event::Reconnect
+                | Event::RetryConnection(_)
+                | Event::CreatePrivateRoom(_)
+                | Event::LoadMessages
+                | Event::FetchMissedMessages
+                | Event::LoadUsers
+                | Event::LoadRooms
+        )
+    }
 }
 
 impl WidgetErrorEvent for Event {
@@ -61,6 +115,15 @@ pub struct User {
     pub username: String,
 }
 
+#[derive(Clone, Debug)]
+pub struct AccountSummary {
+    pub account_id: AccountId,
+    pub username: String,
+    pub profile_name: String,
+    pub unread_messages_count: usize,
+    pub is_active: bool,
+}
+
 #[derive(Clone, Debug)]
 struct Room {
     pub id: Identifier,
@@ -90,20 +153,54 @@ pub struct OutgoingChatMessage {
     pub content: String,
     pub created_at: NaiveDateTime,
     pub state: OutgoingMessageState,
+    pub state_history: Vec<OutgoingStateChange>,
+}
+
+impl OutgoingChatMessage {
+    fn set_state(&mut self, state: OutgoingMessageState) {
+        self.state = state.clone();
+        self.state_history.push(OutgoingStateChange {
+            state,
+            changed_at: Some(Utc::now()),
+        });
+    }
 }
 
+/// A state the message went through, `changed_at` is unknown for states taken from the server
 #[derive(Clone, Debug)]
+pub struct OutgoingStateChange {
+    pub state: OutgoingMessageState,
+    pub changed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum OutgoingMessageState {
+    Failed = 0,
     Created = 1,
     Sent = 2,
     Received = 3,
     Read = 4,
 }
 
+impl OutgoingMessageState {
+    // Only an acknowledgement from the server clears a failure
+    fn is_upgraded_by(&self, state: &OutgoingMessageState) -> bool {
+        match self {
+            OutgoingMessageState::Failed => matches!(
+                state,
+                OutgoingMessageState::Received | OutgoingMessageState::Read
+            ),
+            _ => (self.clone() as u8) < (state.clone() as u8),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum ConnectionState {
     Connected,
+    Connecting,
+    Reconnecting { retry_at: Instant },
     Disconnected,
 }
 
@@ -117,12 +214,40 @@ pub enum ChatAreaState {
 pub struct ChatAreaRoomSelectedState {
     pub room_id: Identifier,
     pub messages: Vec<ChatMessage>,
-    pub messages_page: i32,
+    pub history_cursor: Option<MessagesCursor>,
+    pub history_seek: Option<HistorySeek>,
+    pub load_generation: u64,
+    pub loading_page: Option<u64>,
+    pub has_more_messages: bool,
+    pub unread_message_uuids: HashSet<UuidIdentifier>,
+}
+
+/// Identifies which room selection a messages request was made for
+#[derive(Clone, Copy, Debug)]
+pub struct MessagesLoad {
+    pub room_id: Identifier,
+    pub generation: u64,
+    pub plan: PagePlan,
+}
+
+impl MessagesLoad {
+    fn is_current(&self, chat_area_state: &ChatAreaRoomSelectedState) -> bool {
+        self.room_id == chat_area_state.room_id
+            && self.generation == chat_area_state.load_generation
+    }
+
+    // Only one request per page is in flight within a room selection
+    fn get_handle_key(&self) -> (u64, u64) {
+        (self.generation, self.plan.page)
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct State {
+    pub server: Arc<Server>,
     pub ws_sender: mpsc::UnboundedSender<SendEvent>,
+    pub room_to_open: Option<Identifier>,
+    pub outbox: Outbox,
     input_value: String,
     rooms: Vec<Room>,
     chat_area_state: ChatAreaState,
@@ -130,14 +255,31 @@ pub struct State {
     rooms_scrollable: scrollable::Id,
     messages_scrollable: scrollable::Id,
     connection_state: ConnectionState,
+    connected_at: Option<Instant>,
+    reconnect_attempt: u32,
+    reconnect_generation: u64,
+    awaiting_ack: HashMap<UuidIdentifier, Instant>,
+    // Handed to the websocket controller but not yet written to the socket
+    unsent_message_uuids: HashSet<UuidIdentifier>,
+    failed_message_uuids: HashSet<UuidIdentifier>,
+    health: Health,
+    messages_generation: u64,
+    messages_load_handles: HashMap<(u64, u64), task::Handle>,
+    room_unread_uuids: HashMap<Identifier, HashSet<UuidIdentifier>>,
+    last_message_cursors: HashMap<Identifier, MessagesCursor>,
+    has_connected: bool,
+    is_window_focused: bool,
 }
 
-impl Default for State {
-    fn default() -> Self {
+impl State {
+    pub fn new(server: Arc<Server>, outbox: Outbox) -> Self {
         let ws_sender = create_stub_sender::<SendEvent>();
 
         Self {
+            server,
             ws_sender,
+            room_to_open: None,
+            outbox,
             input_value: "".to_string(),
             rooms: Vec::new(),
             chat_area_state: ChatAreaState::RoomNotSelected,
@@ -145,13 +287,37 @@ impl Default for State {
             rooms_scrollable: scrollable::Id::new("users"),
             messages_scrollable: scrollable::Id::new("messages"),
             connection_state: ConnectionState::Disconnected,
+            connected_at: None,
+            reconnect_attempt: 0,
+            reconnect_generation: 0,
+            awaiting_ack: HashMap::new(),
+            unsent_message_uuids: HashSet::new(),
+            failed_message_uuids: HashSet::new(),
+            health: Health::default(),
+            messages_generation: 0,
+            messages_load_handles: HashMap::new(),
+            room_unread_uuids: HashMap::new(),
+            last_message_cursors: HashMap::new(),
+            has_connected: false,
+            is_window_focused: true,
         }
     }
+
+    pub fn get_unread_messages_count(&self) -> usize {
+        self.room_unread_uuids.values().map(HashSet::len).sum()
+    }
+
+    fn get_room_unread_count(&self, room_id: Identifier) -> usize {
+        self.room_unread_uuids.get(&room_id).map_or(0, HashSet::len)
+    }
+
+    pub fn is_reconnecting(&self) -> bool {
+        matches!(self.connection_state, ConnectionState::Reconnecting { .. })
+    }
 }
 
 pub struct Widget {
     pub theme: theme::ChatTheme,
-    pub http_client: Arc<HttpClient>,
 }
 
 impl Widget {
@@ -168,7 +334,7 @@ impl Widget {
                 iced::Task::none()
             }
             Event::CreatePrivateRoom(user_id) => {
-                task_perform(self.create_room(user_data.token.clone(), user_id))
+                task_perform(self.create_room(state.server.clone(), user_data.clone(), user_id))
             }
             Event::SendMessage => match &mut state.chat_area_state {
                 ChatAreaState::RoomSelected(chat_area_state) => {
@@ -178,36 +344,45 @@ impl Widget {
                         return Task::none();
                     };
 
-                    let uuid = Uuid::new_v4();
-                    let content = input_value.clone();
-                    let message = ChatMessage::Outgoing(OutgoingChatMessage {
-                        uuid,
-                        user_id: user_data.user_id,
-                        content: content.clone(),
+                    let outbox_message = OutboxMessage {
+                        uuid: Uuid::new_v4(),
+                        room_id: chat_area_state.room_id,
+                        content: input_value,
                         created_at: Utc::now().naive_utc(),
-                        state: OutgoingMessageState::Created,
-                    });
+                    };
 
-                    chat_area_state.messages.push(message);
+                    chat_area_state.messages.push(Self::get_pending_message(
+                        &outbox_message,
+                        &user_data,
+                        &state.failed_message_uuids,
+                    ));
 
                     state.input_value.clear();
+                    state.outbox.push(outbox_message.clone());
 
-                    let request = WsMessageRequest {
-                        uuid,
-                        room_id: chat_area_state.room_id,
-                        content,
-                    };
-
-                    let controller_event = ws::controller::SendEvent::Message(request);
+                    // Queued messages are flushed once the connection is back
+                    match state.connection_state {
+                        ConnectionState::Connected => {
+                            let request = WsMessageRequest::from(&outbox_message);
+                            let controller_event = ws::controller::SendEvent::Message(request);
 
-                    Event::SendToWs(controller_event).task()
+                            Event::SendToWs(controller_event)
+                                .task()
+                                .chain(Self::track_sent_message(state, outbox_message.uuid))
+                        }
+                        _ => Task::none(),
+                    }
                 }
                 ChatAreaState::RoomNotSelected => {
                     error_popup::ErrorEvent::String("User is not chosen".to_string()).task()
                 }
             },
             Event::Reconnect => {
-                let ws_url = config::get_variables().ws_url.clone();
+                let ws_url = state.server.profile.ws_url.clone();
+
+                // Any pending retry is superseded by this attempt
+                state.reconnect_generation += 1;
+                state.connection_state = ConnectionState::Connecting;
 
                 let disconnect_event = ws::controller::SendEvent::Disconnect;
                 let connect_event = ws::controller::SendEvent::Connect {
@@ -220,19 +395,26 @@ impl Widget {
 
                 disconnect.task().chain(connect.task())
             }
-            Event::LoadUsers => task_perform(self.load_users(user_data.token.clone())),
-            Event::LoadRooms => task_perform(self.load_rooms(user_data.token.clone())),
-            Event::LoadMessages => match &state.chat_area_state {
-                ChatAreaState::RoomSelected(chat_area_state) => task_perform(self.load_messages(
-                    chat_area_state.room_id,
-                    user_data.token.clone(),
-                    chat_area_state.messages_page as u64,
-                )),
-                ChatAreaState::RoomNotSelected => error_popup::ErrorEvent::String(
-                    "Cannot load messages: no user selected".to_string(),
-                )
-                .task(),
-            },
+            Event::LoadUsers => {
+                task_perform(self.load_users(state.server.clone(), user_data.clone()))
+            }
+            Event::LoadRooms => {
+                task_perform(self.load_rooms(state.server.clone(), user_data.clone()))
+            }
+            Event::LoadMessages => self.load_messages_page(state, &user_data),
+            Event::MessagesLoadFinished(load) => Self::finish_messages_load(state, load),
+            Event::MessagesScrolled(viewport) => Task::batch([
+                Self::check_visible_messages(state),
+                self.load_older_messages_on_scroll(state, &user_data, viewport),
+            ]),
+            Event::MessagesViewportUnfilled(generation) => {
+                self.load_messages_to_fill_viewport(state, &user_data, generation)
+            }
+            Event::CheckVisibleMessages => Self::check_visible_messages(state),
+            Event::MessagesSeen(room_id, message_uuids) => {
+                Self::mark_messages_seen(state, room_id, message_uuids)
+            }
+            Event::WindowFocusChanged(is_focused) => Self::set_window_focus(state, is_focused),
             Event::AddUsers(users_response) => {
                 let current_user_id = user_data.user_id;
                 let users: Vec<User> = users_response
@@ -249,74 +431,73 @@ impl Widget {
 
                 Task::none()
             }
-            Event::AddMessages(messages_response) => match &mut state.chat_area_state {
+            Event::AddMessages(load, messages_response) => match &mut state.chat_area_state {
                 ChatAreaState::RoomSelected(chat_area_state) => {
-                    let from_response_to_message_fn = |response: MessageResponse| {
-                        if user_data.user_id == response.user_id {
-                            let state = if response.read {
-                                OutgoingMessageState::Read
-                            } else {
-                                OutgoingMessageState::Received
-                            };
-
-                            ChatMessage::Outgoing(OutgoingChatMessage {
-                                user_id: response.user_id,
-                                uuid: response.uuid,
-                                content: response.content,
-                                created_at: response.created_at,
-                                state,
-                            })
-                        } else {
-                            ChatMessage::Incoming(IncomingChatMessage {
-                                user_id: response.user_id,
-                                uuid: response.uuid,
-                                content: response.content,
-                                created_at: response.created_at,
-                            })
-                        }
-                    };
+                    if !load.is_current(chat_area_state) {
+                        tracing::debug!(
+                            "Dropping stale messages response for room {}",
+                            load.room_id
+                        );
+
+                        return Task::none();
+                    }
 
                     let new_messages: Vec<ChatMessage> = messages_response
                         .0
                         .iter()
                         .cloned()
-                        .map(from_response_to_message_fn)
+                        .map(|response| Self::get_chat_message(response, &user_data))
+                        .collect();
+
+                    let new_message_uuids: Vec<UuidIdentifier> = messages_response
+                        .0
+                        .iter()
+                        .map(|message| message.uuid)
                         .collect();
 
-                    chat_area_state.messages.extend(new_messages);
-                    chat_area_state
-                        .messages
-                        .sort_by_key(|message| match message {
-                            ChatMessage::Outgoing(message) => message.created_at,
-                            ChatMessage::Incoming(message) => message.created_at,
-                        });
+                    let is_seeking = Self::add_messages_page(chat_area_state, load, new_messages);
 
-                    chat_area_state
-                        .messages
-                        .dedup_by_key(|message| match message {
-                            ChatMessage::Outgoing(message) => message.uuid,
-                            ChatMessage::Incoming(message) => message.uuid,
-                        });
+                    // The page is in, the viewport check below must not wait for MessagesLoadFinished
+                    if !is_seeking {
+                        chat_area_state.loading_page = None;
+                    }
+
+                    // Reported read once they are scrolled into view
+                    chat_area_state.unread_message_uuids.extend(
+                        messages_response
+                            .0
+                            .iter()
+                            .filter(|message| !message.read && message.user_id != user_data.user_id)
+                            .map(|message| message.uuid),
+                    );
 
-                    chat_area_state.messages_page += 1;
+                    let room_id = chat_area_state.room_id;
 
-                    let unread_message_uuids: Vec<UuidIdentifier> = messages_response
+                    if let Some(newest) = messages_response
                         .0
                         .iter()
-                        .filter(|message| !message.read)
-                        .map(|message| message.uuid)
-                        .collect();
+                        .map(MessagesCursor::from_response)
+                        .max()
+                    {
+                        Self::remember_last_message(state, room_id, newest);
+                    }
 
-                    // TODO: read only visible
-                    let ws_request = WsMarkMessagesReadRequest {
-                        room_id: chat_area_state.room_id,
-                        message_uuids: unread_message_uuids,
+                    Self::forget_pending_messages(state, &new_message_uuids);
+
+                    let next_page_task = if is_seeking {
+                        self.load_messages_page(state, &user_data)
+                    } else {
+                        Self::check_messages_fill_viewport(state)
                     };
 
-                    Event::SendToWs(ws::controller::SendEvent::MessagesRead(ws_request)).task()
+                    Task::batch([Event::CheckVisibleMessages.task(), next_page_task])
                 }
                 ChatAreaState::RoomNotSelected => Task::none(),
             },
+            Event::FetchMissedMessages => self.fetch_missed_messages(state, &user_data),
+            Event::AddMissedMessages(missed_messages) => {
+                Self::add_missed_messages(state, &user_data, missed_messages)
+            }
             Event::AddRooms(get_rooms_response) => {
                 let rooms: Vec<Room> = get_rooms_response
                     .0
@@ -329,7 +510,15 @@ impl Widget {
 
                 state.rooms = rooms;
 
-                Task::none()
+                match state.room_to_open.take() {
+                    Some(room_id) if state.rooms.iter().any(|room| room.id == room_id) => {
+                        Event::SelectRoom(room_id).task()
+                    }
+                    Some(room_id) => {
+                        error_popup::ErrorEvent::String(format!("Room {room_id} not found")).task()
+                    }
+                    None => Task::none(),
+                }
             }
             Event::AddCreatedRoom(room_response) => {
                 let room = Room {
@@ -345,28 +534,24 @@ impl Widget {
                     if chat_area_state.room_id == room_id {
                         Task::none()
                     } else {
-                        state.chat_area_state =
-                            ChatAreaState::RoomSelected(ChatAreaRoomSelectedState {
-                                room_id,
-                                messages: Vec::new(),
-                                messages_page: 0,
-                            });
+                        state.chat_area_state = ChatAreaState::RoomSelected(
+                            Self::get_selected_room_state(state, room_id, &user_data),
+                        );
 
                         Event::LoadMessages.task()
                     }
                 }
                 ChatAreaState::RoomNotSelected => {
-                    state.chat_area_state =
-                        ChatAreaState::RoomSelected(ChatAreaRoomSelectedState {
-                            room_id,
-                            messages: Vec::new(),
-                            messages_page: 0,
-                        });
+                    state.chat_area_state = ChatAreaState::RoomSelected(
+                        Self::get_selected_room_state(state, room_id, &user_data),
+                    );
 
                     Event::LoadMessages.task()
                 }
             },
             Event::SendToWs(event) => {
+                inspector::record_ws_sent(&event);
+
                 if let Err(error) = state.ws_sender.send(event) {
                     tracing::error!("Send error {error}");
 
@@ -376,19 +561,65 @@ impl Widget {
                     Task::none()
                 }
             }
+            Event::AckTimedOut(message_uuid) => Self::check_ack_deadline(state, message_uuid),
+            Event::SendFailed => Self::handle_send_failure(state),
+            Event::RetryMessage(message_uuid) => Self::retry_message(state, message_uuid),
+            Event::DeleteMessage(message_uuid) => Self::delete_message(state, message_uuid),
+            Event::CheckHealth => match state.connection_state {
+                ConnectionState::Connected => {
+                    task_perform(self.check_health(state.server.clone(), user_data.clone()))
+                }
+                _ => Task::none(),
+            },
+            Event::HealthChecked(latency) => {
+                Self::record_http_latency(state, latency);
+
+                Task::none()
+            }
             Event::DeselectRoom => {
+                Self::abort_messages_loads(state);
                 state.chat_area_state = ChatAreaState::RoomNotSelected;
 
                 Task::none()
             }
+            Event::RetryConnection(generation) => {
+                if generation != state.reconnect_generation || !state.is_reconnecting() {
+                    return Task::none();
+                }
+
+                state.connection_state = ConnectionState::Connecting;
+
+                task_perform(self.check_connection(state.server.clone(), user_data.clone()))
+            }
+            Event::RetryNow => {
+                if !state.is_reconnecting() {
+                    return Task::none();
+                }
+
+                Event::RetryConnection(state.reconnect_generation).task()
+            }
+            Event::ConnectionFailed => Self::schedule_reconnect(state),
+            Event::SwitchAccount(account_id) => ui::Event::SwitchAccount(account_id).task(),
+            Event::AddAccount => ui::Event::AddAccount.task(),
+            Event::OpenSettings => ui::Event::OpenSettings.task(),
+            Event::Logout => ui::Event::Logout.task(),
         }
     }
 
     pub fn ws_update(
         self: Arc<Self>,
         state: &mut State,
+        user_data: AuthUserData,
         event: ws::controller::Event,
     ) -> Task<ui::Event> {
+        // Neither is a frame from the server, and a disconnect must not look like activity
+        if !matches!(
+            event,
+            ws::controller::Event::Ready(_) | ws::controller::Event::Disconnected
+        ) {
+            Self::record_frame(state);
+        }
+
         match event {
             ws::controller::Event::Ready(ws_sender) => {
                 state.ws_sender = ws_sender;
@@ -397,32 +628,80 @@ impl Widget {
             }
             ws::controller::Event::Connected => {
                 state.connection_state = ConnectionState::Connected;
+                state.connected_at = Some(Instant::now());
+                state.reconnect_generation += 1;
+                state.health = Health {
+                    last_frame_at: state.health.last_frame_at,
+                    ..Health::default()
+                };
 
-                Event::LoadUsers.task().chain(Event::LoadRooms.task())
+                let flush_task = Self::flush_outbox(state);
+
+                // Anything posted while the websocket was down never arrives through it
+                let fetch_missed_task = if state.has_connected {
+                    Event::FetchMissedMessages.task()
+                } else {
+                    Task::none()
+                };
+
+                state.has_connected = true;
+
+                Task::batch([
+                    flush_task.chain(Event::LoadUsers.task().chain(Event::LoadRooms.task())),
+                    Event::CheckHealth.task(),
+                    fetch_missed_task,
+                ])
             }
-            ws::controller::Event::Message(message_response) => match &mut state.chat_area_state {
-                // TODO: change page every page_size
-                ChatAreaState::RoomSelected(chat_area_state) => {
-                    let uuid = message_response.uuid;
-                    let user_message = ChatMessage::Incoming(IncomingChatMessage {
-                        uuid,
-                        user_id: message_response.user_id,
-                        content: message_response.content,
+            ws::controller::Event::Message(message_response) => {
+                Self::remember_last_message(
+                    state,
+                    message_response.room_id,
+                    MessagesCursor {
                         created_at: message_response.created_at,
-                    });
+                        uuid: message_response.uuid,
+                    },
+                );
 
-                    chat_area_state.messages.push(user_message);
+                let room_id = message_response.room_id;
+                let uuid = message_response.uuid;
+                // Messages sent from another session of this account are never unread
+                let is_incoming = message_response.user_id != user_data.user_id;
 
-                    let ws_request = WsMarkMessagesReadRequest {
-                        room_id: chat_area_state.room_id,
-                        message_uuids: vec![uuid],
-                    };
+                match &mut state.chat_area_state {
+                    ChatAreaState::RoomSelected(chat_area_state)
+                        if chat_area_state.room_id == room_id =>
+                    {
+                        let chat_message = Self::get_chat_message(message_response, &user_data);
+
+                        Self::merge_messages(chat_area_state, vec![chat_message]);
+
+                        if is_incoming {
+                            chat_area_state.unread_message_uuids.insert(uuid);
+                        }
 
-                    Event::SendToWs(ws::controller::SendEvent::MessagesRead(ws_request)).task()
+                        Event::CheckVisibleMessages.task()
+                    }
+                    _ => {
+                        if is_incoming {
+                            state
+                                .room_unread_uuids
+                                .entry(room_id)
+                                .or_default()
+                                .insert(uuid);
+                        }
+
+                        // A message can be the first sign of a room created by someone else
+                        if state.rooms.iter().any(|room| room.id == room_id) {
+                            Task::none()
+                        } else {
+                            Event::LoadRooms.task()
+                        }
+                    }
                 }
-                ChatAreaState::RoomNotSelected => Task::none(),
-            },
+            }
             ws::controller::Event::MessageSent(message_uuid) => {
+                state.unsent_message_uuids.remove(&message_uuid);
+
                 Self::change_outgoing_messages_state(
                     state,
                     vec![message_uuid],
@@ -432,6 +711,9 @@ impl Widget {
                 Task::none()
             }
             ws::controller::Event::MessageReceived(message_uuid) => {
+                Self::record_ws_rtt(state, message_uuid);
+                Self::forget_pending_messages(state, &[message_uuid]);
+
                 Self::change_outgoing_messages_state(
                     state,
                     vec![message_uuid],
@@ -447,6 +729,8 @@ impl Widget {
                     return Task::none();
                 }
 
+                Self::forget_pending_messages(state, &response.message_uuids);
+
                 Self::change_outgoing_messages_state(
                     state,
                     response.message_uuids,
@@ -455,11 +739,154 @@ impl Widget {
 
                 Task::none()
             }
-            ws::controller::Event::Disconnected => {
-                state.connection_state = ConnectionState::Disconnected;
+            ws::controller::Event::Disconnected => Self::schedule_reconnect(state),
+        }
+    }
 
-                Task::none()
+    // Sent directly rather than through SendToWs tasks so that the order is kept
+    fn flush_outbox(state: &mut State) -> Task<ui::Event> {
+        let messages = state.outbox.get_messages().to_vec();
+        let mut ack_tasks = Vec::with_capacity(messages.len());
+
+        for message in messages {
+            let controller_event = ws::controller::SendEvent::Message((&message).into());
+            inspector::record_ws_sent(&controller_event);
+
+            if let Err(error) = state.ws_sender.send(controller_event) {
+                tracing::error!("Send error {error}");
+
+                ack_tasks.push(
+                    error_popup::ErrorEvent::String("Unable to connect to server".to_string())
+                        .task(),
+                );
+
+                break;
             }
+
+            state.failed_message_uuids.remove(&message.uuid);
+            Self::set_outgoing_message_state(state, message.uuid, OutgoingMessageState::Created);
+            ack_tasks.push(Self::track_sent_message(state, message.uuid));
+        }
+
+        Task::batch(ack_tasks)
+    }
+
+    // Responses for the previous room are also dropped by the generation check in AddMessages
+    fn abort_messages_loads(state: &mut State) {
+        for (_, handle) in state.messages_load_handles.drain() {
+            handle.abort();
+        }
+    }
+
+    fn get_selected_room_state(
+        state: &mut State,
+        room_id: Identifier,
+        user_data: &AuthUserData,
+    ) -> ChatAreaRoomSelectedState {
+        Self::abort_messages_loads(state);
+        state.messages_generation += 1;
+
+        let messages = state
+            .outbox
+            .get_room_messages(room_id)
+            .map(|message| {
+                Self::get_pending_message(message, user_data, &state.failed_message_uuids)
+            })
+            .collect();
+
+        ChatAreaRoomSelectedState {
+            room_id,
+            messages,
+            history_cursor: None,
+            history_seek: None,
+            load_generation: state.messages_generation,
+            loading_page: None,
+            has_more_messages: true,
+            unread_message_uuids: HashSet::new(),
+        }
+    }
+
+    fn get_pending_message(
+        message: &OutboxMessage,
+        user_data: &AuthUserData,
+        failed_message_uuids: &HashSet<UuidIdentifier>,
+    ) -> ChatMessage {
+        let state = if failed_message_uuids.contains(&message.uuid) {
+            OutgoingMessageState::Failed
+        } else {
+            OutgoingMessageState::Created
+        };
+
+        ChatMessage::Outgoing(OutgoingChatMessage {
+            uuid: message.uuid,
+            user_id: user_data.user_id,
+            content: message.content.clone(),
+            created_at: message.created_at,
+            state_history: Self::get_state_history(&state, message.created_at),
+            state,
+        })
+    }
+
+    fn get_message_uuid(message: &ChatMessage) -> UuidIdentifier {
+        match message {
+            ChatMessage::Outgoing(message) => message.uuid,
+            ChatMessage::Incoming(message) => message.uuid,
+        }
+    }
+
+    fn get_chat_message(response: MessageResponse, user_data: &AuthUserData) -> ChatMessage {
+        if user_data.user_id == response.user_id {
+            let state = if response.read {
+                OutgoingMessageState::Read
+            } else {
+                OutgoingMessageState::Received
+            };
+
+            ChatMessage::Outgoing(OutgoingChatMessage {
+                user_id: response.user_id,
+                uuid: response.uuid,
+                content: response.content,
+                created_at: response.created_at,
+                state_history: Self::get_state_history(&state, response.created_at),
+                state,
+            })
+        } else {
+            ChatMessage::Incoming(IncomingChatMessage {
+                user_id: response.user_id,
+                uuid: response.uuid,
+                content: response.content,
+                created_at: response.created_at,
+            })
+        }
+    }
+
+    // Only the creation time is known for a message that wasn't sent from this session
+    fn get_state_history(
+        state: &OutgoingMessageState,
+        created_at: NaiveDateTime,
+    ) -> Vec<OutgoingStateChange> {
+        let created = OutgoingStateChange {
+            state: OutgoingMessageState::Created,
+            changed_at: Some(created_at.and_utc()),
+        };
+
+        if *state == OutgoingMessageState::Created {
+            vec![created]
+        } else {
+            vec![
+                created,
+                OutgoingStateChange {
+                    state: state.clone(),
+                    changed_at: None,
+                },
+            ]
+        }
+    }
+
+    fn get_message_created_at(message: &ChatMessage) -> NaiveDateTime {
+        match message {
+            ChatMessage::Outgoing(message) => message.created_at,
+            ChatMessage::Incoming(message) => message.created_at,
         }
     }
 
@@ -484,8 +911,8 @@ impl Widget {
                     .collect();
 
                 for message in target_messages {
-                    if (message.state.clone() as u8) < (message_state.clone() as u8) {
-                        message.state = message_state.clone();
+                    if message.state.is_upgraded_by(&message_state) {
+                        message.set_state(message_state.clone());
                     }
                 }
             }
@@ -493,30 +920,54 @@ impl Widget {
         }
     }
 
+    fn get_unexpected_error_event(
+        error: UnexpectedHttpError<AuthenticatedUnexpectedErrorResponse>,
+        retry_event: Event,
+    ) -> ui::Event {
+        match error {
+            UnexpectedHttpError::Api(AuthenticatedUnexpectedErrorResponse::InvalidToken) => {
+                ui::Event::TokenExpired(retry_event)
+            }
+            error => error.into(),
+        }
+    }
+
     async fn load_messages(
         self: Arc<Self>,
-        room_id: Identifier,
-        session_token: BearerToken,
-        page: u64,
+        server: Arc<Server>,
+        load: MessagesLoad,
+        user_data: AuthUserData,
     ) -> MonoResult<ui::Event> {
         let request = GetMessagesRequest {
-            room_id,
-            page,
-            page_size: 20,
+            room_id: load.room_id,
+            page: load.plan.page,
+            page_size: MESSAGES_PAGE_SIZE,
         };
 
-        let result = self.http_client.request(request, session_token).await?;
+        let result = inspector::inspect_http(request, |request| {
+            server.http_client.request(request, user_data.token.clone())
+        })
+        .await
+        .map_err(|error| Self::get_unexpected_error_event(error, Event::LoadMessages))?;
 
         Ok(match result {
-            Ok(response) => Event::AddMessages(response).event(),
+            Ok(response) => Event::AddMessages(load, response).event(),
             Err(error) => error_popup::ErrorEvent::GetMessages(error).event(),
         })
     }
 
-    async fn load_users(self: Arc<Self>, session_token: BearerToken) -> MonoResult<ui::Event> {
+    async fn load_users(
+        self: Arc<Self>,
+        server: Arc<Server>,
+        user_data: AuthUserData,
+    ) -> MonoResult<ui::Event> {
         let request = GetUsersRequest {};
 
-        let result = self.http_client.request(request, session_token).await?;
+        let result = inspector::inspect_http(request, |request| {
+            server.http_client.request(request, user_data.token.clone())
+        })
+        .await
+        .map_err(|error| Self::get_unexpected_error_event(error, Event::LoadUsers))?;
 
         Ok(match result {
             Ok(response) => Event::AddUsers(response).event(),
@@ -524,10 +975,18 @@ impl Widget {
         })
     }
 
-    async fn load_rooms(self: Arc<Self>, session_token: BearerToken) -> MonoResult<ui::Event> {
+    async fn load_rooms(
+        self: Arc<Self>,
+        server: Arc<Server>,
+        user_data: AuthUserData,
+    ) -> MonoResult<ui::Event> {
         let request = GetRoomsRequest {};
 
-        let result = self.http_client.request(request, session_token).await?;
+        let result = inspector::inspect_http(request, |request| {
+            server.http_client.request(request, user_data.token.clone())
+        })
+        .await
+        .map_err(|error| Self::get_unexpected_error_event(error, Event::LoadRooms))?;
 
         Ok(match result {
             Ok(response) => Event::AddRooms(response).event(),
@@ -537,7 +996,8 @@ impl Widget {
 
     async fn create_room(
         self: Arc<Self>,
-        session_token: BearerToken,
+        server: Arc<Server>,
+        user_data: AuthUserData,
         user_id: Identifier,
     ) -> MonoResult<ui::Event> {
         let request = CreatePrivateRoomRequest {
@@ -545,7 +1005,13 @@ impl Widget {
             name: None,
         };
 
-        let result = self.http_client.request(request, session_token).await?;
+        let result = inspector::inspect_http(request, |request| {
+            server.http_client.request(request, user_data.token.clone())
+        })
+        .await
+        .map_err(|error| {
+            Self::get_unexpected_error_event(error, Event::CreatePrivateRoom(user_id))
+        })?;
 
         Ok(match result {
             Ok(response) => Event::AddCreatedRoom(response).event(),
@@ -0,0 +1,83 @@
+use iced::{Task, widget::container};
+use nultr_client_lib::ws;
+use nultr_shared_lib::request::{Identifier, UuidIdentifier, WsMarkMessagesReadRequest};
+
+use crate::ui::{self, WidgetErrorEvent};
+
+use super::{ChatAreaState, Event, State, Widget};
+
+impl Widget {
+    pub(super) fn get_message_container_id(uuid: UuidIdentifier) -> container::Id {
+        container::Id::new(format!("message-{uuid}"))
+    }
+
+    /// Asks the renderer which unread bubbles intersect the messages viewport,
+    /// the answer comes back as a single `MessagesSeen`
+    pub(super) fn check_visible_messages(state: &State) -> Task<ui::Event> {
+        let ChatAreaState::RoomSelected(chat_area_state) = &state.chat_area_state else {
+            return Task::none();
+        };
+
+        if !state.is_window_focused || chat_area_state.unread_message_uuids.is_empty() {
+            return Task::none();
+        }
+
+        let room_id = chat_area_state.room_id;
+        let visibility_tasks = chat_area_state.unread_message_uuids.iter().map(|&uuid| {
+            container::visible_bounds(Self::get_message_container_id(uuid))
+                .map(move |bounds| bounds.map(|_| uuid))
+        });
+
+        Task::batch(visibility_tasks).collect().map(move |uuids| {
+            Event::MessagesSeen(room_id, uuids.into_iter().flatten().collect()).event()
+        })
+    }
+
+    // Checks run concurrently, so only uuids still unread are reported
+    pub(super) fn mark_messages_seen(
+        state: &mut State,
+        room_id: Identifier,
+        uuids: Vec<UuidIdentifier>,
+    ) -> Task<ui::Event> {
+        let ChatAreaState::RoomSelected(chat_area_state) = &mut state.chat_area_state else {
+            return Task::none();
+        };
+
+        if chat_area_state.room_id != room_id || !state.is_window_focused {
+            return Task::none();
+        }
+
+        let message_uuids: Vec<UuidIdentifier> = uuids
+            .into_iter()
+            .filter(|uuid| chat_area_state.unread_message_uuids.remove(uuid))
+            .collect();
+
+        if message_uuids.is_empty() {
+            return Task::none();
+        }
+
+        // The badge only drops for messages that are actually reported read
+        if let Some(room_unread_uuids) = state.room_unread_uuids.get_mut(&room_id) {
+            for uuid in &message_uuids {
+                room_unread_uuids.remove(uuid);
+            }
+
+            if room_unread_uuids.is_empty() {
+                state.room_unread_uuids.remove(&room_id);
+            }
+        }
+
+        let ws_request = WsMarkMessagesReadRequest {
+            room_id,
+            message_uuids,
+        };
+
+        Event::SendToWs(ws::controller::SendEvent::MessagesRead(ws_request)).task()
+    }
+
+    pub(super) fn set_window_focus(state: &mut State, is_focused: bool) -> Task<ui::Event> {
+        state.is_window_focused = is_focused;
+
+        Self::check_visible_messages(state)
+    }
+}
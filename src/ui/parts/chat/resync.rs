@@ -0,0 +1,196 @@
+use std::sync::Arc;
+
+use iced::Task;
+use nultr_shared_lib::{
+    request::{AuthUserData, GetMessagesRequest, Identifier, MessageResponse, UuidIdentifier},
+    util::MonoResult,
+};
+use rust_api_kit::http::client::AuthenticatedHttpClientTrait;
+
+use crate::{
+    inspector,
+    server::Server,
+    ui::{self, WidgetErrorEvent},
+    util::task_perform,
+};
+
+use super::{ChatAreaState, Event, MESSAGES_PAGE_SIZE, MessagesCursor, State, Widget, error_popup};
+
+// Longer outages reload the room instead, the newest messages are what matters after a reconnect
+const MAX_MISSED_MESSAGES_PAGES: u64 = 5;
+
+/// Messages posted in a room after `since`, newest first
+#[derive(Clone, Debug)]
+pub struct MissedMessages {
+    pub room_id: Identifier,
+    pub since: MessagesCursor,
+    pub messages: Vec<MessageResponse>,
+    /// False when the page limit was hit first, so messages between `since`
+    /// and the oldest one here are still missing
+    pub reaches_since: bool,
+}
+
+impl Widget {
+    pub(super) fn remember_last_message(
+        state: &mut State,
+        room_id: Identifier,
+        cursor: MessagesCursor,
+    ) {
+        state
+            .last_message_cursors
+            .entry(room_id)
+            .and_modify(|last_cursor| *last_cursor = (*last_cursor).max(cursor))
+            .or_insert(cursor);
+    }
+
+    /// Fetches what was posted while the websocket was down, for every room a message is known for
+    pub(super) fn fetch_missed_messages(
+        self: Arc<Self>,
+        state: &State,
+        user_data: &AuthUserData,
+    ) -> Task<ui::Event> {
+        let fetch_tasks: Vec<Task<ui::Event>> = state
+            .last_message_cursors
+            .iter()
+            .map(|(&room_id, &since)| {
+                task_perform(self.clone().load_missed_messages(
+                    state.server.clone(),
+                    room_id,
+                    since,
+                    user_data.clone(),
+                ))
+            })
+            .collect();
+
+        Task::batch(fetch_tasks)
+    }
+
+    /// Server copies refresh the state of our own messages, incoming unread ones
+    /// go to the visibility check or to the room's unread count
+    pub(super) fn add_missed_messages(
+        state: &mut State,
+        user_data: &AuthUserData,
+        missed_messages: MissedMessages,
+    ) -> Task<ui::Event> {
+        let MissedMessages {
+            room_id,
+            since,
+            messages,
+            reaches_since,
+        } = missed_messages;
+
+        if let Some(newest) = messages.iter().map(MessagesCursor::from_response).max() {
+            Self::remember_last_message(state, room_id, newest);
+        }
+
+        let message_uuids: Vec<UuidIdentifier> =
+            messages.iter().map(|message| message.uuid).collect();
+        let missed_unread_uuids = messages
+            .iter()
+            .filter(|message| !message.read && message.user_id != user_data.user_id)
+            .filter(|message| MessagesCursor::from_response(message) > since)
+            .map(|message| message.uuid);
+
+        let is_selected_room = matches!(
+            &state.chat_area_state,
+            ChatAreaState::RoomSelected(chat_area_state) if chat_area_state.room_id == room_id
+        );
+
+        // Merging would leave a hole the cursor pages past, so the history starts over
+        if is_selected_room && !reaches_since {
+            tracing::debug!(
+                "Missed messages of room {room_id} don't reach back to {}, reloading it",
+                since.uuid
+            );
+
+            Self::forget_pending_messages(state, &message_uuids);
+            state.chat_area_state = ChatAreaState::RoomSelected(Self::get_selected_room_state(
+                state, room_id, user_data,
+            ));
+
+            return Event::LoadMessages.task();
+        }
+
+        let task = match &mut state.chat_area_state {
+            ChatAreaState::RoomSelected(chat_area_state) if chat_area_state.room_id == room_id => {
+                chat_area_state
+                    .unread_message_uuids
+                    .extend(missed_unread_uuids);
+
+                let chat_messages = messages
+                    .into_iter()
+                    .map(|message| Self::get_chat_message(message, user_data))
+                    .collect();
+
+                Self::merge_messages(chat_area_state, chat_messages);
+
+                Event::CheckVisibleMessages.task()
+            }
+            _ => {
+                state
+                    .room_unread_uuids
+                    .entry(room_id)
+                    .or_default()
+                    .extend(missed_unread_uuids);
+
+                Task::none()
+            }
+        };
+
+        Self::forget_pending_messages(state, &message_uuids);
+
+        task
+    }
+
+    async fn load_missed_messages(
+        self: Arc<Self>,
+        server: Arc<Server>,
+        room_id: Identifier,
+        since: MessagesCursor,
+        user_data: AuthUserData,
+    ) -> MonoResult<ui::Event> {
+        let mut messages: Vec<MessageResponse> = Vec::new();
+        let mut reaches_since = false;
+
+        for page in 0..MAX_MISSED_MESSAGES_PAGES {
+            let request = GetMessagesRequest {
+                room_id,
+                page,
+                page_size: MESSAGES_PAGE_SIZE,
+            };
+
+            let result = inspector::inspect_http(request, |request| {
+                server.http_client.request(request, user_data.token.clone())
+            })
+            .await
+            .map_err(|error| Self::get_unexpected_error_event(error, Event::FetchMissedMessages))?;
+
+            let response = match result {
+                Ok(response) => response,
+                Err(error) => return Ok(error_popup::ErrorEvent::GetMessages(error).event()),
+            };
+
+            // A short page is the start of the history, so nothing older is missing
+            let is_full_page = response.0.len() as u64 >= MESSAGES_PAGE_SIZE;
+            reaches_since = !is_full_page
+                || response
+                    .0
+                    .iter()
+                    .any(|message| MessagesCursor::from_response(message) <= since);
+
+            messages.extend(response.0);
+
+            if reaches_since {
+                break;
+            }
+        }
+
+        Ok(Event::AddMissedMessages(MissedMessages {
+            room_id,
+            since,
+            messages,
+            reaches_since,
+        })
+        .event())
+    }
+}
@@ -1,27 +1,36 @@
+use chrono::{DateTime, Local, NaiveDate};
 use iced::{
     Element, Length, Padding, Theme, alignment,
     widget::{
-        Button, Column, Container, Svg, button, column, container, horizontal_space, row,
-        scrollable, stack, text, text_input, vertical_space,
+        Button, Column, Container, Row, Svg, button, column, container, horizontal_space, row,
+        scrollable, stack, text, text_input, tooltip, vertical_space,
     },
 };
-use nultr_shared_lib::request::AuthUserData;
+use nultr_shared_lib::request::{AuthUserData, UuidIdentifier};
+use std::time::Instant;
+
+use crate::config;
 
 use super::{
-    ChatAreaRoomSelectedState, ChatAreaState, ChatMessage, Event, OutgoingMessageState, Room,
-    State, User, Widget,
+    AccountSummary, ChatAreaRoomSelectedState, ChatAreaState, ChatMessage, ConnectionState, Event,
+    HealthStatus, OutgoingMessageState, OutgoingStateChange, Room, State, User, Widget,
 };
 
 impl Widget {
     #![allow(mismatched_lifetime_syntaxes)]
-    pub fn view(&self, state: &State, _: AuthUserData) -> Element<Event> {
+    pub fn view(
+        &self,
+        state: &State,
+        _: AuthUserData,
+        accounts: Vec<AccountSummary>,
+    ) -> Element<Event> {
         let chat_field_widget = match &state.chat_area_state {
             ChatAreaState::RoomNotSelected => self.get_users_widget(state),
             ChatAreaState::RoomSelected(chat_area_state) => {
                 self.get_chat_widget(state, chat_area_state)
             }
         };
-        let user_container = self.get_rooms_widget(state);
+        let user_container = self.get_rooms_widget(state, accounts);
 
         container(row![
             user_container
@@ -42,10 +51,30 @@ impl Widget {
         state: &State,
         chat_area_state: &ChatAreaRoomSelectedState,
     ) -> Container<'_, Event> {
-        let input_row = match state.connection_state.clone() {
-            super::ConnectionState::Connected => self.get_input_row_widget(state),
-            super::ConnectionState::Disconnected => self.get_connect_btn_widget(),
-        };
+        // The composer stays usable while offline, messages wait in the outbox
+        let connection_status =
+            match state.connection_state.clone() {
+                ConnectionState::Connected => None,
+                ConnectionState::Connecting => {
+                    Some(self.get_connection_status_widget("Connecting...".to_string(), false))
+                }
+                ConnectionState::Reconnecting { retry_at } => {
+                    let seconds_left = retry_at
+                        .saturating_duration_since(Instant::now())
+                        .as_secs_f32()
+                        .ceil();
+
+                    Some(self.get_connection_status_widget(
+                        format!("Reconnecting in {seconds_left}s"),
+                        true,
+                    ))
+                }
+                ConnectionState::Disconnected => Some(self.get_connect_btn_widget()),
+            };
+        let input_row = column![]
+            .push_maybe(connection_status)
+            .push(self.get_input_row_widget(state))
+            .spacing(10);
         let message_container = self.get_messages_widget(state, chat_area_state);
 
         container(stack![
@@ -65,22 +94,41 @@ impl Widget {
         state: &State,
         chat_area_state: &ChatAreaRoomSelectedState,
     ) -> Container<'_, Event> {
-        let message_widgets: Element<_> = chat_area_state
-            .messages
-            .iter()
-            .fold(column![], |col, msg| {
-                let row = self.render_message(msg);
-                col.push(row)
-            })
-            .push(vertical_space().height(90))
-            .into();
+        let loading_row = chat_area_state.loading_page.is_some().then(|| {
+            container(
+                text("Loading messages...")
+                    .size(14)
+                    .color(self.theme.secondary_text),
+            )
+            .align_x(alignment::Horizontal::Center)
+            .width(Length::Fill)
+            .padding(10)
+        });
+
+        let today = Local::now().date_naive();
+        let (message_widgets, _) = chat_area_state.messages.iter().fold(
+            (column![].push_maybe(loading_row), None),
+            |(col, last_date), msg| {
+                let date = get_local_created_at(msg).date_naive();
+                let col = if last_date == Some(date) {
+                    col
+                } else {
+                    col.push(self.get_day_separator_widget(date, today))
+                };
+
+                (col.push(self.render_message(msg)), Some(date))
+            },
+        );
+        let message_widgets: Element<_> = message_widgets.push(vertical_space().height(90)).into();
 
         let scrollable_messages = scrollable(
             container(container(message_widgets).max_width(800))
+                .id(Self::get_messages_content_id())
                 .align_x(alignment::Horizontal::Center)
                 .width(Length::Fill),
         )
         .anchor_bottom()
+        .on_scroll(Event::MessagesScrolled)
         .id(state.messages_scrollable.clone());
 
         container(column![
@@ -89,13 +137,14 @@ impl Widget {
                 .height(Length::Shrink)
                 .width(Length::Fill)
         ])
+        .id(Self::get_messages_area_id())
         .style(|_: &Theme| self.theme.message_container)
     }
 
     fn render_message(&self, msg: &ChatMessage) -> Column<'_, Event> {
-        let get_message_widget = |text| {
+        let get_message_widget = |text, style| {
             container(text)
-                .style(|_| self.theme.message)
+                .style(move |_| style)
                 .width(Length::Shrink)
                 .padding(12)
         };
@@ -103,11 +152,26 @@ impl Widget {
 
         let message_space = horizontal_space().width(Length::FillPortion(7));
 
+        let created_at = get_local_created_at(msg);
+        let time_text = text(created_at.format("%H:%M").to_string())
+            .size(12)
+            .color(self.theme.secondary_text);
+        let message_details = self.get_message_details_widget(msg, created_at);
+
         let message_row = match msg {
             ChatMessage::Outgoing(message_data) => {
                 let message_text = text(message_data.content.clone()).size(16.0);
-                let message = get_message_widget(message_text);
+                let is_failed = matches!(message_data.state, OutgoingMessageState::Failed);
+                let message = get_message_widget(
+                    message_text,
+                    if is_failed {
+                        self.theme.failed_message
+                    } else {
+                        self.theme.message
+                    },
+                );
                 let svg = match message_data.state {
+                    OutgoingMessageState::Failed => self.theme.message_failed_svg.clone(),
                     OutgoingMessageState::Created => self.theme.message_sent_svg.clone(),
                     OutgoingMessageState::Sent => self.theme.message_sent_svg.clone(),
                     OutgoingMessageState::Received => self.theme.message_received_svg.clone(),
@@ -125,21 +189,46 @@ impl Widget {
                 .height(45)
                 .width(Length::Fill);
 
+                let message_stack = stack![
+                    message.align_x(alignment::Horizontal::Right),
+                    status_mark_widget
+                ];
+
+                let failed_message_actions =
+                    is_failed.then(|| self.get_failed_message_actions_widget(message_data.uuid));
+
+                let message_widget = column![
+                    tooltip(message_stack, message_details, tooltip::Position::Top),
+                    time_text
+                ]
+                .push_maybe(failed_message_actions)
+                .spacing(4)
+                .align_x(alignment::Horizontal::Right);
+
                 row![
                     message_space,
-                    get_message_container(stack![
-                        message.align_x(alignment::Horizontal::Right),
-                        status_mark_widget
-                    ])
-                    .align_x(alignment::Horizontal::Right)
+                    get_message_container(message_widget).align_x(alignment::Horizontal::Right)
                 ]
             }
             ChatMessage::Incoming(message_data) => {
                 let text = text(message_data.content.clone()).size(16.0);
 
+                // Tooltips don't pass operations on, so the id the visibility check
+                // looks for goes on a container around it
+                let message_widget = column![
+                    container(tooltip(
+                        stack![get_message_widget(text, self.theme.message)],
+                        message_details,
+                        tooltip::Position::Top,
+                    ))
+                    .id(Self::get_message_container_id(message_data.uuid)),
+                    time_text
+                ]
+                .spacing(4)
+                .align_x(alignment::Horizontal::Left);
+
                 row![
-                    get_message_container(stack![get_message_widget(text)])
-                        .align_x(alignment::Horizontal::Left),
+                    get_message_container(message_widget).align_x(alignment::Horizontal::Left),
                     message_space
                 ]
             }
@@ -155,12 +244,108 @@ impl Widget {
             })
     }
 
-    fn get_rooms_widget(&self, state: &State) -> Container<'_, Event> {
+    fn get_day_separator_widget(&self, date: NaiveDate, today: NaiveDate) -> Container<'_, Event> {
+        let label = if date == today {
+            "Today".to_string()
+        } else if today.pred_opt() == Some(date) {
+            "Yesterday".to_string()
+        } else {
+            date.format("%-d %B %Y").to_string()
+        };
+
+        container(text(label).size(13).color(self.theme.secondary_text))
+            .align_x(alignment::Horizontal::Center)
+            .width(Length::Fill)
+            .padding(Padding {
+                top: 0.0,
+                right: 30.0,
+                bottom: 20.0,
+                left: 30.0,
+            })
+    }
+
+    fn get_message_details_widget(
+        &self,
+        msg: &ChatMessage,
+        created_at: DateTime<Local>,
+    ) -> Container<'_, Event> {
+        let mut details =
+            column![text(created_at.format("%A, %-d %B %Y %H:%M:%S").to_string()).size(13)]
+                .spacing(4);
+
+        if let ChatMessage::Outgoing(message_data) = msg {
+            details = message_data
+                .state_history
+                .iter()
+                .fold(details, |details, change| {
+                    details.push(self.get_state_change_widget(change, created_at))
+                });
+        }
+
+        container(details).padding(10).style(|_| self.theme.tooltip)
+    }
+
+    fn get_state_change_widget(
+        &self,
+        change: &OutgoingStateChange,
+        created_at: DateTime<Local>,
+    ) -> Row<'_, Event> {
+        // States taken from the server have no known time
+        let changed_at = change
+            .changed_at
+            .map(|changed_at| {
+                let changed_at = changed_at.with_timezone(&Local);
+
+                if changed_at.date_naive() == created_at.date_naive() {
+                    changed_at.format("%H:%M:%S").to_string()
+                } else {
+                    changed_at.format("%-d %B %H:%M:%S").to_string()
+                }
+            })
+            .unwrap_or_default();
+
+        row![
+            text(get_state_label(&change.state)).size(13).width(70),
+            text(changed_at).size(13).color(self.theme.secondary_text),
+        ]
+        .spacing(8)
+    }
+
+    fn get_failed_message_actions_widget(&self, uuid: UuidIdentifier) -> Row<'_, Event> {
+        let retry_button = button(text("Retry").size(13))
+            .style(|_, _| self.theme.message_action_btn)
+            .on_press(Event::RetryMessage(uuid));
+
+        let delete_button = button(text("Delete").size(13))
+            .style(|_, _| self.theme.message_action_btn)
+            .on_press(Event::DeleteMessage(uuid));
+
+        row![
+            text("Not sent").size(13).color(self.theme.secondary_text),
+            retry_button,
+            delete_button
+        ]
+        .spacing(5)
+        .align_y(alignment::Vertical::Center)
+    }
+
+    fn get_rooms_widget(
+        &self,
+        state: &State,
+        accounts: Vec<AccountSummary>,
+    ) -> Container<'_, Event> {
+        let show_unread_badges = config::get_config().notifications.show_unread_badges;
+
         let rooms: Element<_> = state
             .rooms
             .iter()
             .fold(column![], |col, user| {
-                let row = self.get_room_widget(&state.chat_area_state, user);
+                let unread_count = if show_unread_badges {
+                    state.get_room_unread_count(user.id)
+                } else {
+                    0
+                };
+                let row = self.get_room_widget(&state.chat_area_state, user, unread_count);
                 col.push(row)
             })
             .into();
@@ -171,26 +356,109 @@ impl Widget {
             .height(Length::Fill);
 
         let show_user_search_btn = self.get_show_user_search_btn_widget();
+        let account_btns = self.get_account_btns_widget();
+        let account_switcher = self.get_account_switcher_widget(accounts);
 
-        container(stack![show_user_search_btn, rooms_scrollable,])
-            .padding(12)
-            .align_y(alignment::Vertical::Top)
-            .style(|_: &Theme| self.theme.rooms_container)
+        let health_widget = self.get_health_widget(state);
+
+        container(stack![
+            show_user_search_btn,
+            account_btns,
+            column![account_switcher, health_widget, rooms_scrollable].spacing(10),
+        ])
+        .padding(12)
+        .align_y(alignment::Vertical::Top)
+        .style(|_: &Theme| self.theme.rooms_container)
+    }
+
+    fn get_health_widget(&self, state: &State) -> Element<'_, Event> {
+        let (status, color) = match state.get_health_status() {
+            HealthStatus::Connected => ("Connected", self.theme.health_connected),
+            HealthStatus::Connecting => ("Connecting", self.theme.health_connecting),
+            HealthStatus::Degraded => ("Degraded", self.theme.health_degraded),
+            HealthStatus::Offline => ("Offline", self.theme.health_offline),
+        };
+
+        let latencies = [
+            ("ws", state.health.ws_rtt),
+            ("http", state.health.http_latency),
+        ]
+        .into_iter()
+        .filter_map(|(name, latency)| {
+            latency.map(|latency| format!("{name} {}ms", latency.as_millis()))
+        })
+        .collect::<Vec<_>>()
+        .join(" · ");
+
+        let status_row = row![
+            text("●").size(12).color(color),
+            text(status).size(14),
+            horizontal_space(),
+            text(latencies).size(12).color(self.theme.secondary_text),
+        ]
+        .spacing(6)
+        .align_y(alignment::Vertical::Center);
+
+        let last_frame = match state.health.last_frame_at {
+            Some(last_frame_at) => format!("Last frame {}s ago", last_frame_at.elapsed().as_secs()),
+            None => "No frames received yet".to_string(),
+        };
+
+        // There is no ping in the websocket protocol, round trips come from message acks
+        let ws_rtt = match state.health.ws_rtt {
+            Some(ws_rtt) => format!(
+                "Websocket round trip {}ms, from the last acknowledged message",
+                ws_rtt.as_millis()
+            ),
+            None => "Websocket round trip is measured once a message is sent".to_string(),
+        };
+
+        let tooltip_content = container(
+            column![
+                text(state.server.profile.ws_url.to_string()).size(13),
+                text(state.server.profile.http_url.to_string()).size(13),
+                text(last_frame).size(13).color(self.theme.secondary_text),
+                text(ws_rtt).size(13).color(self.theme.secondary_text),
+            ]
+            .spacing(4),
+        )
+        .padding(10)
+        .style(|_| self.theme.tooltip);
+
+        tooltip(
+            container(status_row).padding([0, 5]).width(Length::Fill),
+            tooltip_content,
+            tooltip::Position::Bottom,
+        )
+        .into()
     }
 
-    fn get_room_widget(&self, chat_area_state: &ChatAreaState, room: &Room) -> Button<'_, Event> {
+    fn get_room_widget(
+        &self,
+        chat_area_state: &ChatAreaState,
+        room: &Room,
+        unread_count: usize,
+    ) -> Button<'_, Event> {
         let profile_image_btn = button(Svg::new(self.theme.profile_image_svg.clone()))
             .height(40)
             .width(40)
             .style(|_, _| self.theme.profile_image_btn);
 
-        let user_info_widget = container(
-            row![profile_image_btn, text(room.name.clone())]
-                .spacing(10)
-                .align_y(alignment::Vertical::Center),
-        )
-        .padding(5)
-        .align_x(alignment::Horizontal::Left);
+        let mut room_row = row![
+            profile_image_btn,
+            text(room.name.clone()),
+            horizontal_space()
+        ]
+        .spacing(10)
+        .align_y(alignment::Vertical::Center);
+
+        if unread_count > 0 {
+            room_row = room_row.push(self.get_unread_badge_widget(unread_count));
+        }
+
+        let user_info_widget = container(room_row)
+            .padding(5)
+            .align_x(alignment::Horizontal::Left);
 
         let btn_style = match chat_area_state {
             ChatAreaState::RoomSelected(_) => self.theme.active_chat_btn,
@@ -203,6 +471,52 @@ impl Widget {
             .style(move |_, _| btn_style)
     }
 
+    fn get_unread_badge_widget(&self, unread_count: usize) -> Container<'_, Event> {
+        container(text(unread_count.to_string()).size(12))
+            .padding([2, 8])
+            .style(|_| self.theme.unread_badge)
+    }
+
+    fn get_account_switcher_widget(&self, accounts: Vec<AccountSummary>) -> Column<'_, Event> {
+        let account_buttons = accounts.into_iter().fold(column![], |col, account| {
+            let btn_style = if account.is_active {
+                self.theme.active_chat_btn
+            } else {
+                self.theme.chat_btn
+            };
+
+            let account_info = column![
+                text(account.username),
+                text(account.profile_name)
+                    .size(12)
+                    .color(self.theme.secondary_text),
+            ];
+
+            let mut account_row = row![account_info, horizontal_space()]
+                .spacing(10)
+                .align_y(alignment::Vertical::Center);
+
+            if account.unread_messages_count > 0 {
+                account_row =
+                    account_row.push(self.get_unread_badge_widget(account.unread_messages_count));
+            }
+
+            let account_btn = button(account_row)
+                .on_press(Event::SwitchAccount(account.account_id))
+                .width(Length::Fill)
+                .style(move |_, _| btn_style);
+
+            col.push(account_btn)
+        });
+
+        let add_account_btn = button(text("Add account").size(14))
+            .on_press(Event::AddAccount)
+            .width(Length::Fill)
+            .style(|_, _| self.theme.chat_btn);
+
+        column![account_buttons, add_account_btn].spacing(5)
+    }
+
     fn get_users_widget(&self, state: &State) -> Container<'_, Event> {
         let users: Element<_> = state
             .users
@@ -284,6 +598,51 @@ impl Widget {
         .height(Length::Fill)
     }
 
+    pub fn get_account_btns_widget(&self) -> Container<'_, Event> {
+        let logout_btn =
+            button(container(Svg::new(self.theme.logout_svg.clone())).center(Length::Fill))
+                .style(|_, _| self.theme.logout_btn)
+                .on_press(Event::Logout)
+                .height(35)
+                .width(35);
+
+        let settings_btn =
+            button(container(Svg::new(self.theme.settings_svg.clone())).center(Length::Fill))
+                .style(|_, _| self.theme.settings_btn)
+                .on_press(Event::OpenSettings)
+                .height(35)
+                .width(35);
+
+        container(row![logout_btn, settings_btn].spacing(10))
+            .align_x(alignment::Horizontal::Left)
+            .align_y(alignment::Vertical::Bottom)
+            .width(Length::Fill)
+            .height(Length::Fill)
+    }
+
+    pub fn get_connection_status_widget(
+        &self,
+        status: String,
+        is_retryable: bool,
+    ) -> Container<'_, Event> {
+        let mut status_row = row![text(status), horizontal_space()]
+            .spacing(10)
+            .align_y(alignment::Vertical::Center);
+
+        if is_retryable {
+            status_row = status_row.push(
+                button(text("Retry now"))
+                    .style(|_, _| self.theme.connect_btn)
+                    .on_press(Event::RetryNow),
+            );
+        }
+
+        container(status_row)
+            .padding(10)
+            .width(Length::Fill)
+            .style(|_| self.theme.input_container)
+    }
+
     pub fn get_connect_btn_widget(&self) -> Container<'_, Event> {
         container(
             button(text("Connect"))
@@ -297,3 +656,20 @@ impl Widget {
         .style(|_| self.theme.input_container)
     }
 }
+
+// Message times are stored as utc without an offset
+fn get_local_created_at(msg: &ChatMessage) -> DateTime<Local> {
+    Widget::get_message_created_at(msg)
+        .and_utc()
+        .with_timezone(&Local)
+}
+
+fn get_state_label(state: &OutgoingMessageState) -> &'static str {
+    match state {
+        OutgoingMessageState::Failed => "Failed",
+        OutgoingMessageState::Created => "Created",
+        OutgoingMessageState::Sent => "Sent",
+        OutgoingMessageState::Received => "Received",
+        OutgoingMessageState::Read => "Read",
+    }
+}
@@ -9,7 +9,7 @@ use nultr_client_lib::{
 };
 use nultr_shared_lib::request::{
     AuthenticatedUnexpectedErrorResponse, CreatePrivateRoomErrorResponse, GetMessagesErrorResponse,
-    GetRoomsErrorResponse, GetUsersErrorResponse, LoginErrorResponse, UnexpectedErrorResponse,
+    GetRoomsErrorResponse, GetUsersErrorResponse, UnexpectedErrorResponse,
 };
 use rust_api_kit::http::client::{RequestError, UnexpectedHttpError};
 
@@ -36,7 +36,6 @@ pub enum ErrorEvent {
     String(String),
     Unexpected(UnexpectedHttpError<UnexpectedErrorResponse>),
     AuthenticatedUnexpected(UnexpectedHttpError<AuthenticatedUnexpectedErrorResponse>),
-    Login(LoginErrorResponse),
     GetMessages(GetMessagesErrorResponse),
     GetUsers(GetUsersErrorResponse),
     CreateRoom(CreatePrivateRoomErrorResponse),
@@ -118,7 +117,6 @@ impl Widget {
 
                 ErrorEvent::String(message.to_string()).task()
             }
-            ErrorEvent::Login(error) => ErrorEvent::String(error.into_error_message()).task(),
             ErrorEvent::GetUsers(_) => ErrorEvent::String("Unknown error".to_string()).task(),
             ErrorEvent::GetMessages(error) => ErrorEvent::String(error.into_error_message()).task(),
             ErrorEvent::GetRooms(error) => ErrorEvent::String(error.into_error_message()).task(),
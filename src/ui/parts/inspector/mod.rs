@@ -0,0 +1,191 @@
+mod view;
+
+use std::{fmt, sync::Arc};
+
+use iced::Task;
+
+use crate::{
+    inspector::{self, Entry, EntryKind},
+    ui::{self, theme},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KindFilter {
+    All,
+    Http,
+    Websocket,
+}
+
+impl KindFilter {
+    pub const ALL: [KindFilter; 3] = [KindFilter::All, KindFilter::Http, KindFilter::Websocket];
+
+    fn matches(&self, kind: EntryKind) -> bool {
+        match self {
+            KindFilter::All => true,
+            KindFilter::Http => kind == EntryKind::Http,
+            KindFilter::Websocket => matches!(kind, EntryKind::WsSent | EntryKind::WsReceived),
+        }
+    }
+}
+
+impl fmt::Display for KindFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KindFilter::All => write!(f, "All"),
+            KindFilter::Http => write!(f, "Http"),
+            KindFilter::Websocket => write!(f, "Websocket"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    FilterChanged(String),
+    KindFilterSelected(KindFilter),
+    FailuresOnlyToggled(bool),
+    SelectEntry(u64),
+    Clear,
+    Export,
+    Close,
+}
+
+impl Event {
+    pub fn event(self) -> ui::Event {
+        ui::Event::Inspector(self)
+    }
+
+    pub fn task(self) -> Task<ui::Event> {
+        self.event().task()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct State {
+    entries: Vec<Entry>,
+    filter: String,
+    kind_filter: KindFilter,
+    is_failures_only: bool,
+    selected_entry_id: Option<u64>,
+    export_message: Option<String>,
+    error_message: Option<String>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            entries: inspector::get_entries(),
+            filter: String::new(),
+            kind_filter: KindFilter::All,
+            is_failures_only: false,
+            selected_entry_id: None,
+            export_message: None,
+            error_message: None,
+        }
+    }
+}
+
+impl State {
+    /// Takes a fresh snapshot of the captured log, called on every tick while the panel is open
+    pub fn refresh(&mut self) {
+        self.entries = inspector::get_entries();
+    }
+
+    fn get_filtered_entries(&self) -> Vec<&Entry> {
+        let filter = self.filter.trim().to_lowercase();
+
+        self.entries
+            .iter()
+            .filter(|entry| self.kind_filter.matches(entry.kind))
+            .filter(|entry| !self.is_failures_only || entry.is_failure())
+            .filter(|entry| filter.is_empty() || Self::entry_contains(entry, &filter))
+            .collect()
+    }
+
+    fn get_selected_entry(&self) -> Option<&Entry> {
+        let selected_entry_id = self.selected_entry_id?;
+
+        self.entries
+            .iter()
+            .find(|entry| entry.id == selected_entry_id)
+    }
+
+    fn entry_contains(entry: &Entry, filter: &str) -> bool {
+        [
+            Some(&entry.name),
+            Some(&entry.status),
+            entry.request.as_ref(),
+            entry.response.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .any(|value| value.to_lowercase().contains(filter))
+    }
+}
+
+pub struct Widget {
+    pub theme: theme::Inspector,
+}
+
+impl Widget {
+    pub fn update(self: Arc<Self>, state: &mut State, event: Event) -> Task<ui::Event> {
+        if !matches!(event, Event::Export) {
+            state.export_message = None;
+            state.error_message = None;
+        }
+
+        match event {
+            Event::FilterChanged(filter) => {
+                state.filter = filter;
+
+                Task::none()
+            }
+            Event::KindFilterSelected(kind_filter) => {
+                state.kind_filter = kind_filter;
+
+                Task::none()
+            }
+            Event::FailuresOnlyToggled(is_failures_only) => {
+                state.is_failures_only = is_failures_only;
+
+                Task::none()
+            }
+            Event::SelectEntry(entry_id) => {
+                state.selected_entry_id = match state.selected_entry_id {
+                    Some(selected_entry_id) if selected_entry_id == entry_id => None,
+                    _ => Some(entry_id),
+                };
+
+                Task::none()
+            }
+            Event::Clear => {
+                inspector::clear();
+                state.selected_entry_id = None;
+                state.refresh();
+
+                Task::none()
+            }
+            Event::Export => {
+                let entries: Vec<Entry> =
+                    state.get_filtered_entries().into_iter().cloned().collect();
+
+                match inspector::export(&entries) {
+                    Ok(path) => {
+                        state.export_message = Some(format!(
+                            "Exported {} entries to {}",
+                            entries.len(),
+                            path.display()
+                        ));
+                        state.error_message = None;
+                    }
+                    Err(error) => {
+                        state.export_message = None;
+                        state.error_message = Some(error.to_string());
+                    }
+                }
+
+                Task::none()
+            }
+            Event::Close => ui::Event::ToggleInspector.task(),
+        }
+    }
+}
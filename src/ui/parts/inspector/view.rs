@@ -0,0 +1,206 @@
+use chrono::Local;
+use iced::{
+    Element, Length, alignment,
+    widget::{
+        Column, button, checkbox, column, container, horizontal_space, pick_list, row, scrollable,
+        text, text_input,
+    },
+};
+
+use crate::inspector::{Entry, EntryKind};
+
+use super::{Event, KindFilter, State, Widget};
+
+const PANEL_WIDTH: f32 = 560.0;
+
+impl Widget {
+    #![allow(mismatched_lifetime_syntaxes)]
+    pub fn view(&self, state: &State) -> Element<Event> {
+        let header = row![
+            text("Network").size(20),
+            horizontal_space(),
+            button("Clear")
+                .style(|_, _| self.theme.secondary_btn)
+                .on_press(Event::Clear)
+                .padding(8),
+            button("Export")
+                .style(|_, _| self.theme.secondary_btn)
+                .on_press(Event::Export)
+                .padding(8),
+            button("Close")
+                .style(|_, _| self.theme.secondary_btn)
+                .on_press(Event::Close)
+                .padding(8),
+        ]
+        .spacing(10)
+        .align_y(alignment::Vertical::Center);
+
+        let filter_input = text_input("Filter", &state.filter)
+            .on_input(Event::FilterChanged)
+            .padding(8)
+            .size(14)
+            .width(Length::Fill)
+            .style(|_, _| self.theme.input);
+
+        let kind_picker = pick_list(
+            KindFilter::ALL,
+            Some(state.kind_filter),
+            Event::KindFilterSelected,
+        )
+        .padding(8)
+        .text_size(14)
+        .style(|_, _| self.theme.pick_list);
+
+        let failures_checkbox = checkbox("Failures only", state.is_failures_only)
+            .on_toggle(Event::FailuresOnlyToggled)
+            .text_size(14)
+            .style(|_, _| self.theme.checkbox);
+
+        let filters = row![filter_input, kind_picker, failures_checkbox]
+            .spacing(10)
+            .align_y(alignment::Vertical::Center);
+
+        let entries = state.get_filtered_entries();
+        let summary = text(format!(
+            "{} of {} entries, press F12 to close",
+            entries.len(),
+            state.entries.len()
+        ))
+        .size(13)
+        .color(self.theme.secondary_text);
+
+        // Newest first, which is what is usually looked for
+        let entry_list = entries
+            .into_iter()
+            .rev()
+            .fold(column![].spacing(2), |col, entry| {
+                col.push(self.get_entry_widget(entry, state.selected_entry_id == Some(entry.id)))
+            });
+
+        let mut panel = column![header, filters, summary].spacing(10).padding(15);
+
+        if let Some(export_message) = &state.export_message {
+            panel = panel.push(
+                text(export_message.clone())
+                    .size(13)
+                    .color(self.theme.secondary_text),
+            );
+        }
+
+        if let Some(error_message) = &state.error_message {
+            panel = panel.push(
+                text(error_message.clone())
+                    .size(13)
+                    .color(self.theme.error_text),
+            );
+        }
+
+        panel = panel.push(scrollable(entry_list).height(Length::FillPortion(3)));
+
+        if let Some(entry) = state.get_selected_entry() {
+            panel = panel.push(
+                container(scrollable(self.get_entry_details_widget(entry)))
+                    .style(|_| self.theme.details_container)
+                    .padding(10)
+                    .width(Length::Fill)
+                    .height(Length::FillPortion(2)),
+            );
+        }
+
+        container(
+            container(panel)
+                .style(|_| self.theme.panel)
+                .width(PANEL_WIDTH)
+                .height(Length::Fill),
+        )
+        .align_x(alignment::Horizontal::Right)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+
+    fn get_entry_widget(&self, entry: &Entry, is_selected: bool) -> Element<Event> {
+        let status_color = if entry.is_failure() {
+            self.theme.error_text
+        } else {
+            self.theme.secondary_text
+        };
+
+        let duration = entry
+            .duration_ms
+            .map(|duration_ms| format!("{duration_ms} ms"))
+            .unwrap_or_default();
+
+        let content = row![
+            text(
+                entry
+                    .at
+                    .with_timezone(&Local)
+                    .format("%H:%M:%S")
+                    .to_string()
+            )
+            .size(13)
+            .color(self.theme.secondary_text)
+            .width(65),
+            text(get_kind_label(entry.kind)).size(13).width(55),
+            text(entry.name.clone()).size(13).width(Length::Fill),
+            text(entry.status.clone()).size(13).color(status_color),
+            text(duration)
+                .size(13)
+                .color(self.theme.secondary_text)
+                .width(60)
+                .align_x(alignment::Horizontal::Right),
+        ]
+        .spacing(10)
+        .align_y(alignment::Vertical::Center);
+
+        let style = if is_selected {
+            self.theme.selected_entry_btn
+        } else {
+            self.theme.entry_btn
+        };
+
+        button(content)
+            .style(move |_, _| style)
+            .on_press(Event::SelectEntry(entry.id))
+            .padding(6)
+            .width(Length::Fill)
+            .into()
+    }
+
+    fn get_entry_details_widget(&self, entry: &Entry) -> Column<'_, Event> {
+        let mut details = column![
+            text(format!(
+                "{} {} at {}",
+                get_kind_label(entry.kind),
+                entry.name,
+                entry
+                    .at
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S%.3f")
+            ))
+            .size(14)
+        ]
+        .spacing(8);
+
+        if let Some(request) = &entry.request {
+            details = details.push(text("Request").size(14).color(self.theme.secondary_text));
+            details = details.push(text(request.clone()).size(13));
+        }
+
+        if let Some(response) = &entry.response {
+            details = details.push(text("Response").size(14).color(self.theme.secondary_text));
+            details = details.push(text(response.clone()).size(13));
+        }
+
+        details
+    }
+}
+
+fn get_kind_label(kind: EntryKind) -> &'static str {
+    match kind {
+        EntryKind::Http => "http",
+        EntryKind::WsSent => "ws out",
+        EntryKind::WsReceived => "ws in",
+    }
+}
@@ -1,27 +1,52 @@
+mod remember;
+mod restore;
 mod view;
 
 use std::sync::Arc;
 
 use iced::Task;
+use nultr_client_lib::errors::IntoErrorMessage;
 use nultr_shared_lib::{
-    request::{AuthUserData, LoginRequest, LoginResponse},
+    request::{
+        AuthUserData, LoginErrorResponse, LoginRequest, LoginResponse, RegisterErrorResponse,
+        RegisterRequest, RegisterResponse, UnexpectedErrorResponse,
+    },
     util::MonoResult,
 };
-use rust_api_kit::http::client::{BasicHttpClientTrait, HttpClient};
+use rust_api_kit::http::client::{BasicHttpClientTrait, UnexpectedHttpError};
 
 use crate::{
+    config, inspector,
+    server::Server,
+    session::{self, Session},
     ui::{self, WidgetErrorEvent, theme},
     util::task_perform,
 };
 
 use super::error_popup;
 
+const MIN_USERNAME_LENGTH: usize = 3;
+const MIN_PASSWORD_LENGTH: usize = 8;
+
 #[derive(Debug, Clone)]
 pub enum Event {
     UsernameChanged(String),
     PasswordChanged(String),
+    PasswordConfirmationChanged(String),
+    ModeChanged(Mode),
+    ProfileSelected(String),
     InputSubmitted,
     LoginResult(LoginResponse),
+    LoginFailed(LoginErrorResponse),
+    RegistrationResult(RegisterResponse),
+    RegistrationFailed(RegisterErrorResponse),
+    RequestFailed(UnexpectedHttpError<UnexpectedErrorResponse>),
+    RestoreSession(Session),
+    SessionRestored(Session),
+    SessionExpired(Session),
+    Cancel,
+    SessionRestoreFailed(Session, error_popup::ErrorEvent),
+    Remember(remember::Event),
 }
 
 impl Event {
@@ -29,30 +54,78 @@ impl Event {
         ui::Event::LoginForm(self)
     }
 
-    #[allow(dead_code)]
     pub fn task(self) -> Task<ui::Event> {
         self.event().task()
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mode {
+    Login,
+    Registration,
+}
+
+#[derive(Debug, Clone, Default)]
+struct FieldErrors {
+    username: Option<String>,
+    password: Option<String>,
+    password_confirmation: Option<String>,
+    passphrase: Option<String>,
+}
+
+impl FieldErrors {
+    fn is_empty(&self) -> bool {
+        self.username.is_none()
+            && self.password.is_none()
+            && self.password_confirmation.is_none()
+            && self.passphrase.is_none()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct State {
+    server: Arc<Server>,
+    profile_names: Vec<String>,
+    mode: Mode,
     username: String,
     password: String,
+    password_confirmation: String,
+    field_errors: FieldErrors,
+    error_message: Option<String>,
+    is_submitting: bool,
+    restoring_sessions_count: usize,
+    remember: remember::State,
 }
 
 impl Default for State {
     fn default() -> Self {
         Self {
+            server: Server::get_default(),
+            profile_names: config::get_config().get_profile_names(),
+            mode: Mode::Login,
             username: "".to_string(),
             password: "".to_string(),
+            password_confirmation: "".to_string(),
+            field_errors: FieldErrors::default(),
+            error_message: None,
+            is_submitting: false,
+            restoring_sessions_count: 0,
+            remember: remember::State::default(),
+        }
+    }
+}
+
+impl State {
+    // Picks up server urls changed in the settings
+    pub fn refresh_server(&mut self) {
+        if let Some(server) = Server::from_profile_name(&self.server.profile.name) {
+            self.server = server;
         }
     }
 }
 
 pub struct Widget {
     pub theme: theme::LoginForm,
-    pub http_client: Arc<HttpClient>,
 }
 
 impl Widget {
@@ -60,36 +133,248 @@ impl Widget {
         match event {
             Event::UsernameChanged(username) => {
                 state.username = username;
+                state.field_errors.username = None;
 
                 Task::none()
             }
             Event::PasswordChanged(password) => {
                 state.password = password;
+                state.field_errors.password = None;
+
+                Task::none()
+            }
+            Event::PasswordConfirmationChanged(password_confirmation) => {
+                state.password_confirmation = password_confirmation;
+                state.field_errors.password_confirmation = None;
+
+                Task::none()
+            }
+            Event::ModeChanged(mode) => {
+                if state.is_submitting {
+                    return Task::none();
+                }
+
+                state.mode = mode;
+                state.password.clear();
+                state.password_confirmation.clear();
+                state.field_errors = FieldErrors::default();
+                state.error_message = None;
 
                 Task::none()
             }
-            Event::InputSubmitted => task_perform(self.login(state.clone())),
-            Event::LoginResult(response) => {
-                let user_data = AuthUserData {
+            Event::ProfileSelected(profile_name) => {
+                if state.is_submitting || state.server.profile.name == profile_name {
+                    return Task::none();
+                }
+
+                match Server::from_profile_name(&profile_name) {
+                    Some(server) => {
+                        state.server = server;
+                        state.error_message = None;
+
+                        Task::none()
+                    }
+                    None => error_popup::ErrorEvent::String(format!(
+                        "Unknown server profile: {profile_name}"
+                    ))
+                    .task(),
+                }
+            }
+            Event::InputSubmitted => {
+                if state.is_submitting {
+                    return Task::none();
+                }
+
+                state.field_errors = Self::validate(state);
+                state.error_message = None;
+
+                if !state.field_errors.is_empty() {
+                    return Task::none();
+                }
+
+                state.is_submitting = true;
+
+                match state.mode {
+                    Mode::Login => task_perform(self.login(state.clone())),
+                    Mode::Registration => task_perform(self.register(state.clone())),
+                }
+            }
+            Event::LoginResult(response) => Self::authenticate(
+                state,
+                AuthUserData {
                     user_id: response.user_id,
                     token: response.token,
+                },
+            ),
+            Event::LoginFailed(error) => {
+                state.is_submitting = false;
+                state.error_message = Some(error.into_error_message());
+
+                Task::none()
+            }
+            Event::RegistrationResult(response) => Self::authenticate(
+                state,
+                AuthUserData {
+                    user_id: response.user_id,
+                    token: response.token,
+                },
+            ),
+            Event::RegistrationFailed(error) => {
+                state.is_submitting = false;
+                state.error_message = Some(error.into_error_message());
+
+                Task::none()
+            }
+            Event::RequestFailed(error) => {
+                state.is_submitting = false;
+
+                error_popup::ErrorEvent::Unexpected(error).task()
+            }
+            Event::RestoreSession(session) => {
+                match Server::from_profile_name(&session.profile_name) {
+                    Some(server) => {
+                        state.restoring_sessions_count += 1;
+
+                        task_perform(self.restore_session(server, session))
+                    }
+                    None => {
+                        session::forget(&session);
+
+                        error_popup::ErrorEvent::String(format!(
+                            "Session for {} dropped: server profile {} no longer exists",
+                            session.username, session.profile_name
+                        ))
+                        .task()
+                    }
+                }
+            }
+            Event::SessionRestored(session) => {
+                state.restoring_sessions_count = state.restoring_sessions_count.saturating_sub(1);
+
+                ui::Event::Authenticated(session).task()
+            }
+            Event::SessionExpired(session) => {
+                state.restoring_sessions_count = state.restoring_sessions_count.saturating_sub(1);
+
+                session::forget(&session);
+
+                Task::none()
+            }
+            // Only a rejected token needs a new login, anything else is retried by the reconnect loop
+            Event::SessionRestoreFailed(session, error) => {
+                state.restoring_sessions_count = state.restoring_sessions_count.saturating_sub(1);
+
+                Task::batch([ui::Event::Authenticated(session).task(), error.task()])
+            }
+            Event::Remember(event) => self.remember_update(state, event),
+            Event::Cancel => {
+                *state = State {
+                    restoring_sessions_count: state.restoring_sessions_count,
+                    ..State::default()
                 };
 
-                ui::Event::Authenticated(user_data).task()
+                ui::Event::CancelAddAccount.task()
+            }
+        }
+    }
+
+    fn authenticate(state: &mut State, user_data: AuthUserData) -> Task<ui::Event> {
+        let session = Session {
+            profile_name: state.server.profile.name.clone(),
+            username: state.username.trim().to_string(),
+            user_data,
+        };
+
+        session::save(&session);
+
+        let remember_task = Self::get_remember_task(state);
+
+        *state = State {
+            server: state.server.clone(),
+            restoring_sessions_count: state.restoring_sessions_count,
+            ..State::default()
+        };
+
+        Task::batch([ui::Event::Authenticated(session).task(), remember_task])
+    }
+
+    fn validate(state: &State) -> FieldErrors {
+        let username = state.username.trim();
+
+        let passphrase_error = state.remember.validate();
+
+        match state.mode {
+            Mode::Login => FieldErrors {
+                username: username
+                    .is_empty()
+                    .then(|| "Username is required".to_string()),
+                password: state
+                    .password
+                    .is_empty()
+                    .then(|| "Password is required".to_string()),
+                password_confirmation: None,
+                passphrase: passphrase_error,
+            },
+            Mode::Registration => {
+                let username_error = if username.chars().count() < MIN_USERNAME_LENGTH {
+                    Some(format!(
+                        "Username must be at least {MIN_USERNAME_LENGTH} characters long"
+                    ))
+                } else if username.chars().any(char::is_whitespace) {
+                    Some("Username must not contain spaces".to_string())
+                } else {
+                    None
+                };
+
+                let password_error =
+                    (state.password.chars().count() < MIN_PASSWORD_LENGTH).then(|| {
+                        format!("Password must be at least {MIN_PASSWORD_LENGTH} characters long")
+                    });
+
+                let password_confirmation_error = (state.password != state.password_confirmation)
+                    .then(|| "Passwords do not match".to_string());
+
+                FieldErrors {
+                    username: username_error,
+                    password: password_error,
+                    password_confirmation: password_confirmation_error,
+                    passphrase: passphrase_error,
+                }
             }
         }
     }
 
     async fn login(self: Arc<Self>, state: State) -> MonoResult<ui::Event> {
         let request = LoginRequest {
-            username: state.username.clone(),
+            username: state.username.trim().to_string(),
             password: state.password.clone(),
         };
 
-        let result = self.http_client.request(request).await?;
+        let result =
+            inspector::inspect_http(request, |request| state.server.http_client.request(request))
+                .await
+                .map_err(|error| Event::RequestFailed(error).event())?;
+
         Ok(match result {
             Ok(response) => Event::LoginResult(response).event(),
-            Err(error) => error_popup::ErrorEvent::Login(error).event(),
+            Err(error) => Event::LoginFailed(error).event(),
+        })
+    }
+
+    async fn register(self: Arc<Self>, state: State) -> MonoResult<ui::Event> {
+        let request = RegisterRequest {
+            username: state.username.trim().to_string(),
+            password: state.password.clone(),
+        };
+
+        let result =
+            inspector::inspect_http(request, |request| state.server.http_client.request(request))
+                .await
+                .map_err(|error| Event::RequestFailed(error).event())?;
+
+        Ok(match result {
+            Ok(response) => Event::RegistrationResult(response).event(),
+            Err(error) => Event::RegistrationFailed(error).event(),
         })
     }
 }
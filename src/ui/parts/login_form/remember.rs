@@ -0,0 +1,244 @@
+use std::sync::Arc;
+
+use iced::Task;
+use nultr_shared_lib::util::MonoResult;
+
+use crate::{
+    server::Server,
+    ui::{self, WidgetErrorEvent},
+    util::task_perform,
+    vault::{self, Credentials},
+};
+
+use super::{Mode, Widget, error_popup};
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Toggled(bool),
+    PassphraseChanged(String),
+    NewPassphraseChanged(String),
+    UnlockSubmitted,
+    Unlocked(Vec<Credentials>),
+    CredentialsSelected(Credentials),
+    ChangePassphraseSubmitted,
+    PassphraseUpdated,
+    CredentialsSaved,
+    Forget,
+    Failed(String),
+}
+
+impl Event {
+    pub fn event(self) -> ui::Event {
+        super::Event::Remember(self).event()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct State {
+    pub is_enabled: bool,
+    pub vault_exists: bool,
+    pub passphrase: String,
+    pub new_passphrase: String,
+    pub credentials: Option<Vec<Credentials>>,
+    pub error_message: Option<String>,
+    pub is_busy: bool,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            is_enabled: false,
+            vault_exists: vault::exists(),
+            passphrase: "".to_string(),
+            new_passphrase: "".to_string(),
+            credentials: None,
+            error_message: None,
+            is_busy: false,
+        }
+    }
+}
+
+impl State {
+    pub fn is_unlocked(&self) -> bool {
+        self.credentials.is_some()
+    }
+
+    pub fn validate(&self) -> Option<String> {
+        let is_weak = self.passphrase.chars().count() < vault::MIN_PASSPHRASE_LENGTH;
+
+        // An existing vault is checked against its own passphrase on save
+        (self.is_enabled && !self.vault_exists && is_weak)
+            .then(|| vault::Error::WeakPassphrase.to_string())
+    }
+}
+
+impl Widget {
+    pub(super) fn remember_update(
+        self: Arc<Self>,
+        state: &mut super::State,
+        event: Event,
+    ) -> Task<ui::Event> {
+        match event {
+            Event::Toggled(is_enabled) => {
+                state.remember.is_enabled = is_enabled;
+                state.field_errors.passphrase = None;
+
+                Task::none()
+            }
+            Event::PassphraseChanged(passphrase) => {
+                state.remember.passphrase = passphrase;
+                state.remember.error_message = None;
+                state.field_errors.passphrase = None;
+
+                Task::none()
+            }
+            Event::NewPassphraseChanged(new_passphrase) => {
+                state.remember.new_passphrase = new_passphrase;
+                state.remember.error_message = None;
+
+                Task::none()
+            }
+            Event::UnlockSubmitted => {
+                if state.remember.is_busy {
+                    return Task::none();
+                }
+
+                state.remember.is_busy = true;
+                state.remember.error_message = None;
+
+                let passphrase = state.remember.passphrase.clone();
+
+                task_perform(async move {
+                    let credentials = run_blocking(move || vault::unlock(&passphrase))
+                        .await
+                        .map_err(|error| Event::Failed(error).event())?;
+
+                    Ok(Event::Unlocked(credentials).event())
+                })
+            }
+            Event::Unlocked(credentials) => {
+                state.remember.is_busy = false;
+                state.remember.credentials = Some(credentials);
+
+                Task::none()
+            }
+            Event::CredentialsSelected(credentials) => {
+                if state.is_submitting {
+                    return Task::none();
+                }
+
+                if state.server.profile.name != credentials.profile_name {
+                    match Server::from_profile_name(&credentials.profile_name) {
+                        Some(server) => state.server = server,
+                        None => {
+                            return error_popup::ErrorEvent::String(format!(
+                                "Unknown server profile: {}",
+                                credentials.profile_name
+                            ))
+                            .task();
+                        }
+                    }
+                }
+
+                state.mode = Mode::Login;
+                state.username = credentials.username;
+                state.password = credentials.password;
+                state.field_errors = Default::default();
+                state.error_message = None;
+
+                Task::none()
+            }
+            Event::ChangePassphraseSubmitted => {
+                if state.remember.is_busy {
+                    return Task::none();
+                }
+
+                if state.remember.new_passphrase.chars().count() < vault::MIN_PASSPHRASE_LENGTH {
+                    state.remember.error_message = Some(vault::Error::WeakPassphrase.to_string());
+
+                    return Task::none();
+                }
+
+                state.remember.is_busy = true;
+                state.remember.error_message = None;
+
+                let passphrase = state.remember.passphrase.clone();
+                let new_passphrase = state.remember.new_passphrase.clone();
+
+                task_perform(async move {
+                    run_blocking(move || vault::change_passphrase(&passphrase, &new_passphrase))
+                        .await
+                        .map_err(|error| Event::Failed(error).event())?;
+
+                    Ok(Event::PassphraseUpdated.event())
+                })
+            }
+            Event::PassphraseUpdated => {
+                state.remember.is_busy = false;
+                state.remember.passphrase = std::mem::take(&mut state.remember.new_passphrase);
+
+                Task::none()
+            }
+            Event::CredentialsSaved => {
+                state.remember.vault_exists = true;
+
+                Task::none()
+            }
+            Event::Forget => {
+                if let Err(error) = vault::forget() {
+                    return error_popup::ErrorEvent::String(error.to_string()).task();
+                }
+
+                state.remember = State {
+                    vault_exists: false,
+                    ..State::default()
+                };
+
+                Task::none()
+            }
+            Event::Failed(error_message) => {
+                state.remember.is_busy = false;
+                state.remember.error_message = Some(error_message);
+
+                Task::none()
+            }
+        }
+    }
+
+    pub(super) fn get_remember_task(state: &super::State) -> Task<ui::Event> {
+        if !state.remember.is_enabled {
+            return Task::none();
+        }
+
+        let passphrase = state.remember.passphrase.clone();
+        let credentials = Credentials {
+            profile_name: state.server.profile.name.clone(),
+            username: state.username.trim().to_string(),
+            password: state.password.clone(),
+        };
+
+        task_perform(save_credentials(passphrase, credentials))
+    }
+}
+
+async fn save_credentials(passphrase: String, credentials: Credentials) -> MonoResult<ui::Event> {
+    run_blocking(move || vault::remember(&passphrase, credentials))
+        .await
+        .map_err(|error| {
+            error_popup::ErrorEvent::String(format!("Credentials were not saved: {error}")).event()
+        })?;
+
+    Ok(Event::CredentialsSaved.event())
+}
+
+// Key derivation is deliberately slow, so keep it off the async executor
+async fn run_blocking<T, F>(function: F) -> Result<T, String>
+where
+    F: FnOnce() -> Result<T, vault::Error> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(function)
+        .await
+        .map_err(|error| error.to_string())?
+        .map_err(|error| error.to_string())
+}
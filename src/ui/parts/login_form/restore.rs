@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use nultr_shared_lib::{
+    request::{AuthenticatedUnexpectedErrorResponse, GetUsersRequest},
+    util::MonoResult,
+};
+use rust_api_kit::http::client::{AuthenticatedHttpClientTrait, UnexpectedHttpError};
+
+use crate::{inspector, server::Server, session::Session, ui};
+
+use super::{Event, Widget, error_popup};
+
+impl Widget {
+    // Kept apart from the login request because both client traits expose `request`
+    pub(super) async fn restore_session(
+        self: Arc<Self>,
+        server: Arc<Server>,
+        session: Session,
+    ) -> MonoResult<ui::Event> {
+        let request = GetUsersRequest {};
+
+        let result = inspector::inspect_http(request, |request| {
+            server
+                .http_client
+                .request(request, session.user_data.token.clone())
+        })
+        .await;
+
+        Ok(match result {
+            Ok(Ok(_)) => Event::SessionRestored(session).event(),
+            Ok(Err(error)) => {
+                Event::SessionRestoreFailed(session, error_popup::ErrorEvent::GetUsers(error))
+                    .event()
+            }
+            Err(UnexpectedHttpError::Api(AuthenticatedUnexpectedErrorResponse::InvalidToken)) => {
+                Event::SessionExpired(session).event()
+            }
+            Err(error) => Event::SessionRestoreFailed(
+                session,
+                error_popup::ErrorEvent::AuthenticatedUnexpected(error),
+            )
+            .event(),
+        })
+    }
+}
@@ -1,45 +1,27 @@
 use iced::{
     Element, Length, alignment,
-    widget::{button, column, container, text_input},
+    widget::{
+        Column, button, checkbox, column, container, horizontal_space, pick_list, row, text,
+        text_input,
+    },
 };
 
-use super::{Event, State, Widget};
+use super::{Event, Mode, State, Widget, remember};
 
 impl Widget {
     #![allow(mismatched_lifetime_syntaxes)]
-    pub fn view(&self, state: &State) -> Element<Event> {
-        let username_input = text_input("Type username", state.username.as_str())
-            .on_input(Event::UsernameChanged)
-            .padding(10)
-            .size(16)
-            .width(Length::Fill)
-            .style(|_, _| self.theme.input);
-
-        let password_input = text_input("Type password", state.password.as_str())
-            .on_input(Event::PasswordChanged)
-            .padding(10)
-            .size(16)
-            .width(Length::Fill)
-            .secure(true)
-            .style(|_, _| self.theme.input);
-
-        let send_button = button("Log in")
-            .style(|_, _| self.theme.login_btn)
-            .on_press(Event::InputSubmitted)
-            .padding(10);
+    pub fn view(&self, state: &State, is_cancelable: bool) -> Element<Event> {
+        let form = if state.restoring_sessions_count > 0 {
+            column![text("Restoring session...")]
+        } else {
+            self.get_form_widget(state, is_cancelable)
+        };
 
         container(
             container(
-                column![
-                    username_input,
-                    password_input,
-                    container(send_button)
-                        .width(Length::Fill)
-                        .align_x(alignment::Horizontal::Right),
-                ]
-                .align_x(alignment::Horizontal::Center)
-                .spacing(10)
-                .padding(20),
+                form.align_x(alignment::Horizontal::Center)
+                    .spacing(10)
+                    .padding(20),
             )
             .style(|_| self.theme.form_container)
             .width(500),
@@ -52,4 +34,246 @@ impl Widget {
         .height(Length::Fill)
         .into()
     }
+
+    fn get_form_widget(&self, state: &State, is_cancelable: bool) -> Column<'_, Event> {
+        let is_editable = !state.is_submitting;
+
+        let username_input = self.get_input_widget(
+            "Type username",
+            &state.username,
+            is_editable.then_some(Event::UsernameChanged),
+            false,
+            &state.field_errors.username,
+        );
+
+        let password_input = self.get_input_widget(
+            "Type password",
+            &state.password,
+            is_editable.then_some(Event::PasswordChanged),
+            true,
+            &state.field_errors.password,
+        );
+
+        let (send_label, switch_mode_label, switch_mode) = match (&state.mode, is_editable) {
+            (Mode::Login, true) => ("Log in", "Create account", Mode::Registration),
+            (Mode::Login, false) => ("Logging in...", "Create account", Mode::Registration),
+            (Mode::Registration, true) => ("Sign up", "Back to login", Mode::Login),
+            (Mode::Registration, false) => ("Signing up...", "Back to login", Mode::Login),
+        };
+
+        let send_button = button(send_label)
+            .style(|_, _| self.theme.login_btn)
+            .on_press_maybe(is_editable.then_some(Event::InputSubmitted))
+            .padding(10);
+
+        let switch_mode_button = button(switch_mode_label)
+            .style(|_, _| self.theme.switch_mode_btn)
+            .on_press_maybe(is_editable.then_some(Event::ModeChanged(switch_mode)))
+            .padding(10);
+
+        let mut form = column![];
+
+        if state.profile_names.len() > 1 {
+            let profile_picker = pick_list(
+                state.profile_names.clone(),
+                Some(state.server.profile.name.clone()),
+                Event::ProfileSelected,
+            )
+            .padding(10)
+            .width(Length::Fill)
+            .style(|_, _| self.theme.profile_picker);
+
+            form = form.push(profile_picker);
+        }
+
+        form = form.push(username_input).push(password_input);
+
+        if state.mode == Mode::Registration {
+            let password_confirmation_input = self.get_input_widget(
+                "Repeat password",
+                &state.password_confirmation,
+                is_editable.then_some(Event::PasswordConfirmationChanged),
+                true,
+                &state.field_errors.password_confirmation,
+            );
+
+            form = form.push(password_confirmation_input);
+        }
+
+        form = form.push(self.get_remember_widget(state, is_editable));
+
+        if let Some(error_message) = &state.error_message {
+            form = form.push(
+                text(error_message.clone())
+                    .size(14)
+                    .color(self.theme.error_text),
+            );
+        }
+
+        let mut buttons_row = row![switch_mode_button, horizontal_space()]
+            .width(Length::Fill)
+            .spacing(10)
+            .align_y(alignment::Vertical::Center);
+
+        if is_cancelable {
+            let cancel_button = button("Cancel")
+                .style(|_, _| self.theme.switch_mode_btn)
+                .on_press_maybe(is_editable.then_some(Event::Cancel))
+                .padding(10);
+
+            buttons_row = buttons_row.push(cancel_button);
+        }
+
+        form.push(buttons_row.push(send_button))
+    }
+
+    fn get_remember_widget(&self, state: &State, is_editable: bool) -> Column<'_, Event> {
+        let remember = &state.remember;
+        let is_editable = is_editable && !remember.is_busy;
+
+        let remember_checkbox = checkbox("Remember me", remember.is_enabled)
+            .on_toggle_maybe(
+                is_editable
+                    .then_some(|is_enabled| Event::Remember(remember::Event::Toggled(is_enabled))),
+            )
+            .style(|_, _| self.theme.checkbox);
+
+        let mut remember_column = column![remember_checkbox].spacing(10);
+
+        let passphrase_placeholder = if remember.vault_exists {
+            "Type vault passphrase"
+        } else {
+            "Choose vault passphrase"
+        };
+
+        let is_passphrase_needed =
+            (remember.is_enabled || remember.vault_exists) && !remember.is_unlocked();
+
+        if is_passphrase_needed {
+            let passphrase_input = self.get_input_widget(
+                passphrase_placeholder,
+                &remember.passphrase,
+                is_editable.then_some(|passphrase| {
+                    Event::Remember(remember::Event::PassphraseChanged(passphrase))
+                }),
+                true,
+                &state.field_errors.passphrase,
+            );
+
+            remember_column = remember_column.push(passphrase_input);
+        }
+
+        if remember.vault_exists && !remember.is_unlocked() {
+            let unlock_button = button(if remember.is_busy {
+                "Unlocking..."
+            } else {
+                "Unlock saved logins"
+            })
+            .style(|_, _| self.theme.switch_mode_btn)
+            .on_press_maybe(
+                is_editable.then_some(Event::Remember(remember::Event::UnlockSubmitted)),
+            )
+            .padding(10);
+
+            remember_column = remember_column.push(unlock_button);
+        }
+
+        if let Some(credentials) = &remember.credentials {
+            let credentials_row = credentials
+                .iter()
+                .fold(row![].spacing(10), |credentials_row, credentials| {
+                    let label = if state.profile_names.len() > 1 {
+                        format!("{} ({})", credentials.username, credentials.profile_name)
+                    } else {
+                        credentials.username.clone()
+                    };
+
+                    credentials_row.push(
+                        button(text(label))
+                            .style(|_, _| self.theme.switch_mode_btn)
+                            .on_press_maybe(is_editable.then(|| {
+                                Event::Remember(remember::Event::CredentialsSelected(
+                                    credentials.clone(),
+                                ))
+                            }))
+                            .padding(10),
+                    )
+                })
+                .wrap();
+
+            let new_passphrase_input = text_input("New vault passphrase", &remember.new_passphrase)
+                .on_input_maybe(is_editable.then_some(|new_passphrase| {
+                    Event::Remember(remember::Event::NewPassphraseChanged(new_passphrase))
+                }))
+                .on_submit_maybe(
+                    is_editable
+                        .then_some(Event::Remember(remember::Event::ChangePassphraseSubmitted)),
+                )
+                .padding(10)
+                .size(16)
+                .width(Length::Fill)
+                .secure(true)
+                .style(|_, _| self.theme.input);
+
+            let change_passphrase_button = button("Change passphrase")
+                .style(|_, _| self.theme.switch_mode_btn)
+                .on_press_maybe(
+                    is_editable
+                        .then_some(Event::Remember(remember::Event::ChangePassphraseSubmitted)),
+                )
+                .padding(10);
+
+            remember_column = remember_column.push(credentials_row).push(
+                row![new_passphrase_input, change_passphrase_button]
+                    .spacing(10)
+                    .align_y(alignment::Vertical::Center),
+            );
+        }
+
+        if remember.vault_exists {
+            let forget_button = button("Forget saved logins")
+                .style(|_, _| self.theme.switch_mode_btn)
+                .on_press_maybe(is_editable.then_some(Event::Remember(remember::Event::Forget)))
+                .padding(10);
+
+            remember_column = remember_column.push(forget_button);
+        }
+
+        if let Some(error_message) = &remember.error_message {
+            remember_column = remember_column.push(
+                text(error_message.clone())
+                    .size(13)
+                    .color(self.theme.error_text),
+            );
+        }
+
+        remember_column
+    }
+
+    fn get_input_widget(
+        &self,
+        placeholder: &str,
+        value: &str,
+        on_input: Option<impl Fn(String) -> Event + Copy + 'static>,
+        is_secure: bool,
+        error: &Option<String>,
+    ) -> Column<'_, Event> {
+        let input = text_input(placeholder, value)
+            .on_input_maybe(on_input)
+            .on_submit_maybe(on_input.map(|_| Event::InputSubmitted))
+            .padding(10)
+            .size(16)
+            .width(Length::Fill)
+            .secure(is_secure)
+            .style(|_, _| self.theme.input);
+
+        let mut input_column = column![input].spacing(4);
+
+        if let Some(error) = error {
+            input_column =
+                input_column.push(text(error.clone()).size(13).color(self.theme.error_text));
+        }
+
+        input_column
+    }
 }
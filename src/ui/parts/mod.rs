@@ -1,3 +1,6 @@
 pub mod chat;
 pub mod error_popup;
+pub mod inspector;
 pub mod login_form;
+pub mod reauth_dialog;
+pub mod settings;
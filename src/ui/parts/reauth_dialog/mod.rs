@@ -0,0 +1,136 @@
+mod view;
+
+use std::sync::Arc;
+
+use iced::Task;
+use nultr_client_lib::errors::IntoErrorMessage;
+use nultr_shared_lib::{
+    request::{
+        AuthUserData, Identifier, LoginErrorResponse, LoginRequest, LoginResponse,
+        UnexpectedErrorResponse,
+    },
+    util::MonoResult,
+};
+use rust_api_kit::http::client::{BasicHttpClientTrait, UnexpectedHttpError};
+
+use crate::{
+    inspector,
+    server::Server,
+    ui::{self, WidgetErrorEvent, theme},
+    util::task_perform,
+};
+
+use super::error_popup;
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    PasswordChanged(String),
+    InputSubmitted,
+    LoginResult(LoginResponse),
+    LoginFailed(LoginErrorResponse),
+    RequestFailed(UnexpectedHttpError<UnexpectedErrorResponse>),
+    Logout,
+}
+
+#[derive(Debug, Clone)]
+pub struct State {
+    server: Arc<Server>,
+    user_id: Identifier,
+    username: String,
+    password: String,
+    error_message: Option<String>,
+    is_submitting: bool,
+}
+
+impl State {
+    pub fn new(server: Arc<Server>, user_id: Identifier, username: String) -> Self {
+        Self {
+            server,
+            user_id,
+            username,
+            password: "".to_string(),
+            error_message: None,
+            is_submitting: false,
+        }
+    }
+    pub fn set_server(&mut self, server: Arc<Server>) {
+        self.server = server;
+    }
+}
+
+pub struct Widget {
+    pub theme: theme::ReauthDialog,
+}
+
+impl Widget {
+    pub fn update(self: Arc<Self>, state: &mut State, event: Event) -> Task<ui::Event> {
+        match event {
+            Event::PasswordChanged(password) => {
+                state.password = password;
+
+                Task::none()
+            }
+            Event::InputSubmitted => {
+                if state.is_submitting {
+                    return Task::none();
+                }
+
+                if state.password.is_empty() {
+                    state.error_message = Some("Password is required".to_string());
+
+                    return Task::none();
+                }
+
+                state.is_submitting = true;
+                state.error_message = None;
+
+                task_perform(self.login(state.clone()))
+            }
+            Event::LoginResult(response) => {
+                state.is_submitting = false;
+
+                if response.user_id != state.user_id {
+                    state.error_message = Some("Logged in as a different user".to_string());
+
+                    return Task::none();
+                }
+
+                let user_data = AuthUserData {
+                    user_id: response.user_id,
+                    token: response.token,
+                };
+
+                ui::Event::Reauthenticated(user_data).task()
+            }
+            Event::LoginFailed(error) => {
+                state.is_submitting = false;
+                state.error_message = Some(error.into_error_message());
+
+                Task::none()
+            }
+            Event::RequestFailed(error) => {
+                state.is_submitting = false;
+
+                error_popup::ErrorEvent::Unexpected(error).task()
+            }
+            Event::Logout => ui::Event::Logout.task(),
+        }
+    }
+
+    async fn login(self: Arc<Self>, state: State) -> MonoResult<ui::Event> {
+        let request = LoginRequest {
+            username: state.username.clone(),
+            password: state.password.clone(),
+        };
+
+        let result =
+            inspector::inspect_http(request, |request| state.server.http_client.request(request))
+                .await
+                .map_err(|error| ui::Event::ReauthDialog(Event::RequestFailed(error)))?;
+
+        Ok(match result {
+            Ok(response) => ui::Event::ReauthDialog(Event::LoginResult(response)),
+            Err(error) => ui::Event::ReauthDialog(Event::LoginFailed(error)),
+        })
+    }
+}
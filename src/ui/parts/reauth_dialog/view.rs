@@ -0,0 +1,79 @@
+use iced::{
+    Element, Length, alignment,
+    widget::{button, column, container, horizontal_space, row, text, text_input},
+};
+
+use super::{Event, State, Widget};
+
+impl Widget {
+    #![allow(mismatched_lifetime_syntaxes)]
+    pub fn view(&self, state: &State) -> Element<Event> {
+        let username_input = text_input("Username", state.username.as_str())
+            .padding(10)
+            .size(16)
+            .width(Length::Fill)
+            .style(|_, _| self.theme.input);
+
+        let mut password_input = text_input("Type password", state.password.as_str())
+            .padding(10)
+            .size(16)
+            .width(Length::Fill)
+            .secure(true)
+            .style(|_, _| self.theme.input);
+
+        let mut submit_button = button(if state.is_submitting {
+            "Logging in..."
+        } else {
+            "Log in"
+        })
+        .style(|_, _| self.theme.submit_btn)
+        .padding(10);
+
+        if !state.is_submitting {
+            password_input = password_input
+                .on_input(Event::PasswordChanged)
+                .on_submit(Event::InputSubmitted);
+            submit_button = submit_button.on_press(Event::InputSubmitted);
+        }
+
+        let logout_button = button("Log out")
+            .style(|_, _| self.theme.secondary_btn)
+            .on_press(Event::Logout)
+            .padding(10);
+
+        let mut dialog = column![
+            text("Session expired").size(20),
+            text("Log in again to continue where you left off").size(14),
+            username_input,
+            password_input,
+        ]
+        .spacing(10)
+        .padding(20);
+
+        if let Some(error_message) = &state.error_message {
+            dialog = dialog.push(
+                text(error_message.clone())
+                    .size(14)
+                    .color(self.theme.error_text),
+            );
+        }
+
+        dialog = dialog.push(
+            row![logout_button, horizontal_space(), submit_button]
+                .width(Length::Fill)
+                .align_y(alignment::Vertical::Center),
+        );
+
+        container(
+            container(dialog)
+                .style(|_| self.theme.dialog_container)
+                .width(450),
+        )
+        .style(|_| self.theme.overlay)
+        .align_y(alignment::Vertical::Center)
+        .align_x(alignment::Horizontal::Center)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
@@ -0,0 +1,228 @@
+mod view;
+
+use std::sync::Arc;
+
+use iced::Task;
+
+use crate::{
+    config::{self, Config, Notifications, ServerProfile, SettingsChanges, ThemeName},
+    ui::{self, theme},
+};
+
+const LOG_LEVELS: [tracing::Level; 5] = [
+    tracing::Level::ERROR,
+    tracing::Level::WARN,
+    tracing::Level::INFO,
+    tracing::Level::DEBUG,
+    tracing::Level::TRACE,
+];
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    HttpUrlChanged(usize, String),
+    WsUrlChanged(usize, String),
+    LogLevelSelected(tracing::Level),
+    ThemeSelected(ThemeName),
+    FontSizeChanged(String),
+    ShowUnreadBadgesToggled(bool),
+    Save,
+    Close,
+}
+
+impl Event {
+    pub fn event(self) -> ui::Event {
+        ui::Event::Settings(self)
+    }
+
+    pub fn task(self) -> Task<ui::Event> {
+        self.event().task()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ProfileDraft {
+    name: String,
+    http_url: String,
+    ws_url: String,
+    http_url_error: Option<String>,
+    ws_url_error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct State {
+    profiles: Vec<ProfileDraft>,
+    log_level: tracing::Level,
+    theme: ThemeName,
+    font_size: String,
+    font_size_error: Option<String>,
+    show_unread_badges: bool,
+    error_message: Option<String>,
+    is_saved: bool,
+}
+
+impl State {
+    pub fn new(config: &Config) -> Self {
+        let profiles = config
+            .profiles
+            .iter()
+            .map(|profile| ProfileDraft {
+                name: profile.name.clone(),
+                http_url: profile.http_url.to_string(),
+                ws_url: profile.ws_url.to_string(),
+                http_url_error: None,
+                ws_url_error: None,
+            })
+            .collect();
+
+        Self {
+            profiles,
+            log_level: config.log_level,
+            theme: config.theme,
+            font_size: config.font_size.to_string(),
+            font_size_error: None,
+            show_unread_badges: config.notifications.show_unread_badges,
+            error_message: None,
+            is_saved: false,
+        }
+    }
+}
+
+pub struct Widget {
+    pub theme: theme::Settings,
+}
+
+impl Widget {
+    pub fn update(self: Arc<Self>, state: &mut State, event: Event) -> Task<ui::Event> {
+        if !matches!(event, Event::Save | Event::Close) {
+            state.is_saved = false;
+            state.error_message = None;
+        }
+
+        match event {
+            Event::HttpUrlChanged(index, http_url) => {
+                if let Some(profile) = state.profiles.get_mut(index) {
+                    profile.http_url = http_url;
+                    profile.http_url_error = None;
+                }
+
+                Task::none()
+            }
+            Event::WsUrlChanged(index, ws_url) => {
+                if let Some(profile) = state.profiles.get_mut(index) {
+                    profile.ws_url = ws_url;
+                    profile.ws_url_error = None;
+                }
+
+                Task::none()
+            }
+            Event::LogLevelSelected(log_level) => {
+                state.log_level = log_level;
+
+                Task::none()
+            }
+            Event::ThemeSelected(theme) => {
+                state.theme = theme;
+
+                Task::none()
+            }
+            Event::FontSizeChanged(font_size) => {
+                state.font_size = font_size;
+                state.font_size_error = None;
+
+                Task::none()
+            }
+            Event::ShowUnreadBadgesToggled(show_unread_badges) => {
+                state.show_unread_badges = show_unread_badges;
+
+                Task::none()
+            }
+            Event::Save => {
+                let Some(changes) = Self::get_validated_changes(state) else {
+                    return Task::none();
+                };
+
+                let changed_profile_names: Vec<String> = changes
+                    .profiles
+                    .iter()
+                    .map(|profile| profile.name.clone())
+                    .collect();
+
+                match config::save(changes) {
+                    Ok(_) => {
+                        state.is_saved = true;
+
+                        ui::Event::SettingsSaved(changed_profile_names).task()
+                    }
+                    Err(error) => {
+                        state.error_message = Some(error.to_string());
+
+                        Task::none()
+                    }
+                }
+            }
+            Event::Close => ui::Event::CloseSettings.task(),
+        }
+    }
+
+    // Compared against the effective config, so values overridden on the command line
+    // are only written to the file when they were edited here
+    fn get_validated_changes(state: &mut State) -> Option<SettingsChanges> {
+        let config = config::get_config();
+        let mut changed_profiles = Vec::new();
+        let mut is_valid = true;
+
+        for profile_draft in state.profiles.iter_mut() {
+            let http_url = config::parse_url(&profile_draft.http_url, config::HTTP_URL_SCHEMES)
+                .inspect_err(|error| profile_draft.http_url_error = Some(error.to_string()))
+                .ok();
+            let ws_url = config::parse_url(&profile_draft.ws_url, config::WS_URL_SCHEMES)
+                .inspect_err(|error| profile_draft.ws_url_error = Some(error.to_string()))
+                .ok();
+
+            let (Some(http_url), Some(ws_url)) = (http_url, ws_url) else {
+                is_valid = false;
+                continue;
+            };
+
+            if let Some(profile) = config.get_profile(&profile_draft.name)
+                && (profile.http_url != http_url || profile.ws_url != ws_url)
+            {
+                changed_profiles.push(ServerProfile {
+                    http_url,
+                    ws_url,
+                    ..profile.clone()
+                });
+            }
+        }
+
+        let font_size = match state.font_size.trim().parse::<u16>() {
+            Ok(font_size)
+                if (config::MIN_FONT_SIZE..=config::MAX_FONT_SIZE).contains(&font_size) =>
+            {
+                font_size
+            }
+            _ => {
+                state.font_size_error = Some(format!(
+                    "Font size must be a number between {} and {}",
+                    config::MIN_FONT_SIZE,
+                    config::MAX_FONT_SIZE
+                ));
+                is_valid = false;
+
+                config.font_size
+            }
+        };
+
+        let changes = SettingsChanges {
+            profiles: changed_profiles,
+            log_level: (state.log_level != config.log_level).then_some(state.log_level),
+            theme: state.theme,
+            font_size,
+            notifications: Notifications {
+                show_unread_badges: state.show_unread_badges,
+            },
+        };
+
+        is_valid.then_some(changes)
+    }
+}
@@ -0,0 +1,163 @@
+use iced::{
+    Element, Length, alignment,
+    widget::{
+        Column, button, checkbox, column, container, horizontal_space, pick_list, row, scrollable,
+        text, text_input,
+    },
+};
+
+use crate::config::ThemeName;
+
+use super::{Event, LOG_LEVELS, ProfileDraft, State, Widget};
+
+impl Widget {
+    #![allow(mismatched_lifetime_syntaxes)]
+    pub fn view(&self, state: &State) -> Element<Event> {
+        let profiles = state
+            .profiles
+            .iter()
+            .enumerate()
+            .fold(column![].spacing(20), |col, (index, profile)| {
+                col.push(self.get_profile_widget(index, profile))
+            });
+
+        let log_level_picker =
+            pick_list(LOG_LEVELS, Some(state.log_level), Event::LogLevelSelected)
+                .padding(10)
+                .style(|_, _| self.theme.pick_list);
+
+        let theme_picker = pick_list(ThemeName::ALL, Some(state.theme), Event::ThemeSelected)
+            .padding(10)
+            .style(|_, _| self.theme.pick_list);
+
+        let font_size_input = self.get_input_widget(
+            "Font size",
+            &state.font_size,
+            Event::FontSizeChanged,
+            &state.font_size_error,
+        );
+
+        let show_unread_badges_checkbox =
+            checkbox("Show unread message badges", state.show_unread_badges)
+                .on_toggle(Event::ShowUnreadBadgesToggled)
+                .style(|_, _| self.theme.checkbox);
+
+        let mut form = column![
+            text("Servers").size(20),
+            profiles,
+            text("Appearance").size(20),
+            self.get_labeled_widget("Theme", theme_picker),
+            self.get_labeled_widget("Font size", font_size_input),
+            text("Font size is applied after restart")
+                .size(13)
+                .color(self.theme.secondary_text),
+            text("Notifications").size(20),
+            show_unread_badges_checkbox,
+            text("Diagnostics").size(20),
+            self.get_labeled_widget("Log level", log_level_picker),
+        ]
+        .spacing(10);
+
+        if let Some(error_message) = &state.error_message {
+            form = form.push(
+                text(error_message.clone())
+                    .size(14)
+                    .color(self.theme.error_text),
+            );
+        }
+
+        if state.is_saved {
+            form = form.push(
+                text("Settings saved")
+                    .size(14)
+                    .color(self.theme.secondary_text),
+            );
+        }
+
+        let close_button = button("Close")
+            .style(|_, _| self.theme.secondary_btn)
+            .on_press(Event::Close)
+            .padding(10);
+
+        let save_button = button("Save")
+            .style(|_, _| self.theme.save_btn)
+            .on_press(Event::Save)
+            .padding(10);
+
+        form = form.push(
+            row![horizontal_space(), close_button, save_button]
+                .spacing(10)
+                .align_y(alignment::Vertical::Center),
+        );
+
+        container(scrollable(
+            container(
+                container(form.padding(20))
+                    .style(|_| self.theme.form_container)
+                    .width(600),
+            )
+            .align_x(alignment::Horizontal::Center)
+            .padding(20)
+            .width(Length::Fill),
+        ))
+        .style(|_| self.theme.background)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+
+    fn get_profile_widget(&self, index: usize, profile: &ProfileDraft) -> Column<'_, Event> {
+        column![
+            text(profile.name.clone()).color(self.theme.secondary_text),
+            self.get_input_widget(
+                "Http url",
+                &profile.http_url,
+                move |http_url| Event::HttpUrlChanged(index, http_url),
+                &profile.http_url_error,
+            ),
+            self.get_input_widget(
+                "Websocket url",
+                &profile.ws_url,
+                move |ws_url| Event::WsUrlChanged(index, ws_url),
+                &profile.ws_url_error,
+            ),
+        ]
+        .spacing(10)
+    }
+
+    fn get_labeled_widget<'a>(
+        &'a self,
+        label: &'a str,
+        widget: impl Into<Element<'a, Event>>,
+    ) -> Element<'a, Event> {
+        row![text(label).width(150), widget.into()]
+            .spacing(10)
+            .align_y(alignment::Vertical::Center)
+            .into()
+    }
+
+    fn get_input_widget(
+        &self,
+        placeholder: &str,
+        value: &str,
+        on_input: impl Fn(String) -> Event + 'static,
+        error: &Option<String>,
+    ) -> Column<'_, Event> {
+        let input = text_input(placeholder, value)
+            .on_input(on_input)
+            .on_submit(Event::Save)
+            .padding(10)
+            .size(16)
+            .width(Length::Fill)
+            .style(|_, _| self.theme.input);
+
+        let mut input_column = column![input].spacing(4);
+
+        if let Some(error) = error {
+            input_column =
+                input_column.push(text(error.clone()).size(13).color(self.theme.error_text));
+        }
+
+        input_column
+    }
+}
@@ -1,19 +1,30 @@
+mod palette;
+
 use iced::{
     Background, Border, Color, Shadow,
     border::Radius,
-    widget::{button, container, svg, text_input},
+    widget::{button, checkbox, container, pick_list, svg, text_input},
 };
-use nultr_procmacro_lib::{color, svg_handle};
+use nultr_procmacro_lib::svg_handle;
+
+use crate::config::ThemeName;
+
+use palette::Palette;
 
 pub struct Collection {
     pub app: App,
     pub chat: ChatTheme,
     pub error_popup: ErrorPopup,
+    pub inspector: Inspector,
     pub login_form: LoginForm,
+    pub reauth_dialog: ReauthDialog,
+    pub settings: Settings,
 }
 
-impl Default for Collection {
-    fn default() -> Collection {
+impl Collection {
+    pub fn new(theme_name: ThemeName) -> Collection {
+        let palette = Palette::new(theme_name);
+
         let error_popup = ErrorPopup {
             message_container: container::Style {
                 background: Some(Background::Color([0.4, 0.0, 0.0, 0.7].into())),
@@ -43,32 +54,62 @@ impl Default for Collection {
                 radius: Radius::new(10),
                 ..Border::default()
             },
-            background: Background::Color(color!("#303030")),
-            icon: color!("#D3D3D3"),
-            placeholder: color!("#505050"),
-            value: color!("#D3D3D3"),
-            selection: color!("#3584E4"),
+            background: Background::Color(palette.field),
+            icon: palette.text,
+            placeholder: palette.placeholder,
+            value: palette.text,
+            selection: palette.selection,
         };
 
         let chat_btn = button::Style {
-            background: Some(Background::Color(color!("#181818"))),
-            text_color: color!("#D3D3D3"),
+            background: Some(Background::Color(palette.background)),
+            text_color: palette.text,
             border: Border {
                 radius: Radius::new(10),
                 ..Border::default()
             },
             ..button::Style::default()
         };
+        let message = container::Style {
+            text_color: Some(palette.on_bubble),
+            background: Some(Background::Color(palette.bubble)),
+            border: Border {
+                radius: Radius::new(10),
+                ..Border::default()
+            },
+            ..container::Style::default()
+        };
         let chat = ChatTheme {
             send_btn_svg: svg_handle!("arrow-up-from-dot"),
             create_room_svg: svg_handle!("plus"),
+            logout_svg: svg_handle!("log-out"),
+            settings_svg: svg_handle!("settings"),
             message_sent_svg: svg_handle!("sent"),
             message_read_svg: svg_handle!("read"),
             message_received_svg: svg_handle!("received"),
+            message_failed_svg: svg_handle!("failed"),
             profile_image_svg: svg_handle!("user"),
             show_user_search_btn: button::Style {
-                background: Some(Background::Color(color!("#D3D3D3"))),
-                text_color: color!("#000000"),
+                background: Some(Background::Color(palette.accent)),
+                text_color: palette.on_accent,
+                border: Border {
+                    radius: Radius::new(100),
+                    ..Border::default()
+                },
+                shadow: Shadow::default(),
+            },
+            logout_btn: button::Style {
+                background: Some(Background::Color(palette.accent)),
+                text_color: palette.on_accent,
+                border: Border {
+                    radius: Radius::new(100),
+                    ..Border::default()
+                },
+                shadow: Shadow::default(),
+            },
+            settings_btn: button::Style {
+                background: Some(Background::Color(palette.accent)),
+                text_color: palette.on_accent,
                 border: Border {
                     radius: Radius::new(100),
                     ..Border::default()
@@ -76,8 +117,8 @@ impl Default for Collection {
                 shadow: Shadow::default(),
             },
             send_btn: button::Style {
-                background: Some(Background::Color(color!("#D3D3D3"))),
-                text_color: color!("#000000"),
+                background: Some(Background::Color(palette.accent)),
+                text_color: palette.on_accent,
                 border: Border {
                     radius: Radius::new(100),
                     ..Border::default()
@@ -86,12 +127,12 @@ impl Default for Collection {
             },
             chat_btn,
             active_chat_btn: button::Style {
-                background: Some(Background::Color(color!("#212121"))),
+                background: Some(Background::Color(palette.surface)),
                 ..chat_btn
             },
             profile_image_btn: button::Style {
-                background: Some(Background::Color(color!("#D3D3D3"))),
-                text_color: color!("#D3D3D3"),
+                background: Some(Background::Color(palette.accent)),
+                text_color: palette.text,
                 border: Border {
                     radius: Radius::new(100),
                     ..Border::default()
@@ -99,8 +140,8 @@ impl Default for Collection {
                 shadow: Shadow::default(),
             },
             connect_btn: button::Style {
-                background: Some(Background::Color(color!("#ECECEC"))),
-                text_color: color!("#1F1F1F"),
+                background: Some(Background::Color(palette.highlight)),
+                text_color: palette.on_highlight,
                 border: Border {
                     radius: Radius::new(10),
                     ..Border::default()
@@ -109,8 +150,8 @@ impl Default for Collection {
             },
             input,
             input_container: container::Style {
-                text_color: Some(color!("#D3D3D3")),
-                background: Some(Background::Color(color!("#303030"))),
+                text_color: Some(palette.text),
+                background: Some(Background::Color(palette.field)),
                 border: Border {
                     radius: Radius::new(30),
                     ..Border::default()
@@ -118,44 +159,97 @@ impl Default for Collection {
                 ..container::Style::default()
             },
             rooms_container: container::Style {
-                text_color: Some(color!("#D3D3D3")),
-                background: Some(Background::Color(color!("#181818"))),
+                text_color: Some(palette.text),
+                background: Some(Background::Color(palette.background)),
                 ..container::Style::default()
             },
             users_container: container::Style {
-                text_color: Some(color!("#D3D3D3")),
-                background: Some(Background::Color(color!("#212121"))),
+                text_color: Some(palette.text),
+                background: Some(Background::Color(palette.surface)),
                 ..container::Style::default()
             },
             message_container: container::Style {
-                text_color: Some(color!("#D3D3D3")),
-                background: Some(Background::Color(color!("#212121"))),
+                text_color: Some(palette.text),
+                background: Some(Background::Color(palette.surface)),
+                ..container::Style::default()
+            },
+            secondary_text: palette.secondary_text,
+            unread_badge: container::Style {
+                text_color: Some(palette.on_accent),
+                background: Some(Background::Color(palette.accent)),
+                border: Border {
+                    radius: Radius::new(100),
+                    ..Border::default()
+                },
                 ..container::Style::default()
             },
-            message: container::Style {
-                text_color: Some(Color::from_rgb(200.0, 44.0, 0.0)),
-                background: Some(Background::Color(Color::from_rgb(0.3, 0.3, 0.3))),
+            message,
+            failed_message: container::Style {
+                border: Border {
+                    color: palette.error,
+                    width: 1.0,
+                    radius: Radius::new(10),
+                },
+                ..message
+            },
+            health_connected: palette.success,
+            health_connecting: palette.secondary_text,
+            health_degraded: palette.warning,
+            health_offline: palette.error,
+            tooltip: container::Style {
+                text_color: Some(palette.text),
+                background: Some(Background::Color(palette.field)),
                 border: Border {
                     radius: Radius::new(10),
                     ..Border::default()
                 },
                 ..container::Style::default()
             },
+            message_action_btn: button::Style {
+                background: None,
+                text_color: palette.secondary_text,
+                ..button::Style::default()
+            },
         };
 
         let login_form = LoginForm {
             login_btn: button::Style {
-                background: Some(Background::Color(color!("#D3D3D3"))),
-                text_color: color!("#000000"),
+                background: Some(Background::Color(palette.accent)),
+                text_color: palette.on_accent,
                 border: Border {
                     radius: Radius::new(10),
                     ..Border::default()
                 },
                 shadow: Shadow::default(),
             },
+            switch_mode_btn: button::Style {
+                background: None,
+                text_color: palette.secondary_text,
+                ..button::Style::default()
+            },
+            error_text: palette.error,
+            profile_picker: pick_list::Style {
+                text_color: palette.text,
+                placeholder_color: palette.placeholder,
+                handle_color: palette.text,
+                background: Background::Color(palette.field),
+                border: Border {
+                    radius: Radius::new(10),
+                    ..Border::default()
+                },
+            },
+            checkbox: checkbox::Style {
+                background: Background::Color(palette.field),
+                icon_color: palette.text,
+                border: Border {
+                    radius: Radius::new(4),
+                    ..Border::default()
+                },
+                text_color: Some(palette.text),
+            },
             form_container: container::Style {
-                text_color: Some(color!("#D3D3D3")),
-                background: Some(Background::Color(color!("#212121"))),
+                text_color: Some(palette.text),
+                background: Some(Background::Color(palette.surface)),
                 border: Border {
                     radius: Radius::new(10),
                     ..Border::default()
@@ -163,16 +257,71 @@ impl Default for Collection {
                 ..container::Style::default()
             },
             background: container::Style {
-                background: Some(Background::Color(color!("#181818"))),
+                background: Some(Background::Color(palette.background)),
+                ..container::Style::default()
+            },
+            input,
+        };
+
+        let reauth_dialog = ReauthDialog {
+            overlay: container::Style {
+                background: Some(Background::Color([0.0, 0.0, 0.0, 0.6].into())),
                 ..container::Style::default()
             },
+            dialog_container: login_form.form_container,
+            submit_btn: login_form.login_btn,
+            secondary_btn: login_form.switch_mode_btn,
+            error_text: login_form.error_text,
             input,
         };
 
+        let settings = Settings {
+            background: login_form.background,
+            form_container: login_form.form_container,
+            save_btn: login_form.login_btn,
+            secondary_btn: login_form.switch_mode_btn,
+            input,
+            pick_list: login_form.profile_picker,
+            checkbox: login_form.checkbox,
+            error_text: login_form.error_text,
+            secondary_text: palette.secondary_text,
+        };
+
+        let inspector = Inspector {
+            panel: container::Style {
+                text_color: Some(palette.text),
+                background: Some(Background::Color(palette.surface)),
+                ..container::Style::default()
+            },
+            details_container: container::Style {
+                text_color: Some(palette.text),
+                background: Some(Background::Color(palette.field)),
+                border: Border {
+                    radius: Radius::new(10),
+                    ..Border::default()
+                },
+                ..container::Style::default()
+            },
+            entry_btn: button::Style {
+                background: None,
+                ..chat.chat_btn
+            },
+            selected_entry_btn: button::Style {
+                background: Some(Background::Color(palette.field)),
+                ..chat.chat_btn
+            },
+            secondary_btn: login_form.switch_mode_btn,
+            input,
+            pick_list: login_form.profile_picker,
+            checkbox: login_form.checkbox,
+            error_text: login_form.error_text,
+            secondary_text: palette.secondary_text,
+        };
+
         let app = App {
             background: container::Style {
-                text_color: Some(color!("#FFFFFF")),
-                background: Some(Background::Color(color!("#181818"))),
+                text_color: Some(palette.strong_text),
+                background: Some(Background::Color(palette.background)),
                 border: Border {
                     ..Border::default()
                 },
@@ -184,7 +333,10 @@ impl Default for Collection {
             app,
             chat,
             error_popup,
+            inspector,
             login_form,
+            reauth_dialog,
+            settings,
         }
     }
 }
@@ -198,12 +350,17 @@ pub struct ErrorPopup {
 pub struct ChatTheme {
     pub send_btn_svg: svg::Handle,
     pub create_room_svg: svg::Handle,
+    pub logout_svg: svg::Handle,
+    pub settings_svg: svg::Handle,
     pub message_sent_svg: svg::Handle,
     pub message_received_svg: svg::Handle,
     pub message_read_svg: svg::Handle,
+    pub message_failed_svg: svg::Handle,
     pub send_btn: button::Style,
     pub connect_btn: button::Style,
     pub show_user_search_btn: button::Style,
+    pub logout_btn: button::Style,
+    pub settings_btn: button::Style,
     pub profile_image_svg: svg::Handle,
     pub profile_image_btn: button::Style,
     pub chat_btn: button::Style,
@@ -214,13 +371,60 @@ pub struct ChatTheme {
     pub input_container: container::Style,
     pub message_container: container::Style,
     pub message: container::Style,
+    pub failed_message: container::Style,
+    pub message_action_btn: button::Style,
+    pub health_connected: Color,
+    pub health_connecting: Color,
+    pub health_degraded: Color,
+    pub health_offline: Color,
+    pub tooltip: container::Style,
+    pub unread_badge: container::Style,
+    pub secondary_text: Color,
 }
 
 pub struct LoginForm {
     pub background: container::Style,
     pub form_container: container::Style,
     pub login_btn: button::Style,
+    pub switch_mode_btn: button::Style,
+    pub error_text: Color,
+    pub profile_picker: pick_list::Style,
+    pub checkbox: checkbox::Style,
+    pub input: text_input::Style,
+}
+
+pub struct ReauthDialog {
+    pub overlay: container::Style,
+    pub dialog_container: container::Style,
+    pub submit_btn: button::Style,
+    pub secondary_btn: button::Style,
+    pub error_text: Color,
+    pub input: text_input::Style,
+}
+
+pub struct Settings {
+    pub background: container::Style,
+    pub form_container: container::Style,
+    pub save_btn: button::Style,
+    pub secondary_btn: button::Style,
+    pub input: text_input::Style,
+    pub pick_list: pick_list::Style,
+    pub checkbox: checkbox::Style,
+    pub error_text: Color,
+    pub secondary_text: Color,
+}
+
+pub struct Inspector {
+    pub panel: container::Style,
+    pub details_container: container::Style,
+    pub entry_btn: button::Style,
+    pub selected_entry_btn: button::Style,
+    pub secondary_btn: button::Style,
     pub input: text_input::Style,
+    pub pick_list: pick_list::Style,
+    pub checkbox: checkbox::Style,
+    pub error_text: Color,
+    pub secondary_text: Color,
 }
 
 pub struct App {
@@ -0,0 +1,78 @@
+use iced::Color;
+use nultr_procmacro_lib::color;
+
+use crate::config::ThemeName;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Palette {
+    pub background: Color,
+    pub surface: Color,
+    pub field: Color,
+    pub text: Color,
+    pub strong_text: Color,
+    pub secondary_text: Color,
+    pub placeholder: Color,
+    pub accent: Color,
+    pub on_accent: Color,
+    pub highlight: Color,
+    pub on_highlight: Color,
+    pub bubble: Color,
+    pub on_bubble: Color,
+    pub selection: Color,
+    pub error: Color,
+    pub success: Color,
+    pub warning: Color,
+}
+
+impl Palette {
+    pub fn new(theme_name: ThemeName) -> Self {
+        match theme_name {
+            ThemeName::Dark => Self::dark(),
+            ThemeName::Light => Self::light(),
+        }
+    }
+
+    fn dark() -> Self {
+        Self {
+            background: color!("#181818"),
+            surface: color!("#212121"),
+            field: color!("#303030"),
+            text: color!("#D3D3D3"),
+            strong_text: color!("#FFFFFF"),
+            secondary_text: color!("#9E9E9E"),
+            placeholder: color!("#505050"),
+            accent: color!("#D3D3D3"),
+            on_accent: color!("#000000"),
+            highlight: color!("#ECECEC"),
+            on_highlight: color!("#1F1F1F"),
+            bubble: color!("#4D4D4D"),
+            on_bubble: color!("#FFFFFF"),
+            selection: color!("#3584E4"),
+            error: color!("#E06C75"),
+            success: color!("#4CAF50"),
+            warning: color!("#E5C07B"),
+        }
+    }
+
+    fn light() -> Self {
+        Self {
+            background: color!("#F2F2F2"),
+            surface: color!("#FFFFFF"),
+            field: color!("#E4E4E4"),
+            text: color!("#1F1F1F"),
+            strong_text: color!("#000000"),
+            secondary_text: color!("#6B6B6B"),
+            placeholder: color!("#9A9A9A"),
+            accent: color!("#303030"),
+            on_accent: color!("#FFFFFF"),
+            highlight: color!("#1F1F1F"),
+            on_highlight: color!("#ECECEC"),
+            bubble: color!("#DADADA"),
+            on_bubble: color!("#000000"),
+            selection: color!("#3584E4"),
+            error: color!("#C0392B"),
+            success: color!("#2E7D32"),
+            warning: color!("#B7791F"),
+        }
+    }
+}
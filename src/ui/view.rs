@@ -1,7 +1,8 @@
+use super::{AccountId, AuthState, Event, Ui, chat};
 use iced::widget::container;
 use iced::{Element, widget::stack};
 
-use super::{AuthState, Event, Ui};
+use crate::config;
 
 impl Ui {
     #![allow(mismatched_lifetime_syntaxes)]
@@ -11,18 +12,76 @@ impl Ui {
             .view(&self.state.error_popup)
             .map(Event::ErrorPopup);
 
-        let page = match self.auth_state.clone() {
-            AuthState::Authenticated(user_data) => {
-                self.chat.view(&self.state.chat, user_data).map(Event::Chat)
+        let page = match (self.auth_state.clone(), &self.state.settings) {
+            (AuthState::Authenticated(_), Some(settings_state)) => {
+                self.settings.view(settings_state).map(Event::Settings)
             }
-            AuthState::Unauthenticated => self
-                .login
-                .view(&self.state.login_form)
-                .map(Event::LoginForm),
+            (AuthState::Authenticated(account_id), None) => match self
+                .accounts
+                .iter()
+                .find(|account| account.id == account_id)
+            {
+                Some(account) => {
+                    let chat_page = self
+                        .chat
+                        .view(
+                            &account.chat,
+                            account.session.user_data.clone(),
+                            self.get_account_summaries(account_id),
+                        )
+                        .map(Event::Chat);
+
+                    match &account.reauth_dialog {
+                        Some(reauth_dialog_state) => {
+                            let reauth_dialog = self
+                                .reauth_dialog
+                                .view(reauth_dialog_state)
+                                .map(Event::ReauthDialog);
+
+                            stack![chat_page, reauth_dialog].into()
+                        }
+                        None => chat_page,
+                    }
+                }
+                None => self.get_login_page(false),
+            },
+            (AuthState::AddingAccount(_), _) => self.get_login_page(true),
+            (AuthState::Unauthenticated, _) => self.get_login_page(false),
         };
 
-        container(stack![page, error_popup])
+        let inspector = self
+            .state
+            .inspector
+            .as_ref()
+            .map(|inspector_state| self.inspector.view(inspector_state).map(Event::Inspector));
+
+        container(stack![page].push_maybe(inspector).push(error_popup))
             .style(|_| self.theme.background)
             .into()
     }
+
+    fn get_login_page(&self, is_cancelable: bool) -> Element<Event> {
+        self.login
+            .view(&self.state.login_form, is_cancelable)
+            .map(Event::LoginForm)
+    }
+
+    fn get_account_summaries(&self, active_account_id: AccountId) -> Vec<chat::AccountSummary> {
+        let show_unread_badges = config::get_config().notifications.show_unread_badges;
+
+        self.accounts
+            .iter()
+            .map(|account| chat::AccountSummary {
+                account_id: account.id,
+                username: account.session.username.clone(),
+                profile_name: account.session.profile_name.clone(),
+                unread_messages_count: if show_unread_badges {
+                    account.chat.get_unread_messages_count()
+                } else {
+                    0
+                },
+                is_active: account.id == active_account_id,
+            })
+            .collect()
+    }
 }
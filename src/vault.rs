@@ -0,0 +1,291 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    AeadCore, Key, KeyInit, XChaCha20Poly1305, XNonce,
+    aead::{Aead, OsRng, Payload, rand_core::RngCore},
+};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::storage;
+
+const VAULT_FILE_NAME: &str = "vault.json";
+const VAULT_VERSION: u32 = 1;
+const SALT_LENGTH: usize = 16;
+const KEY_LENGTH: usize = 32;
+
+pub const MIN_PASSPHRASE_LENGTH: usize = 8;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Credential vault not found")]
+    NotFound,
+    #[error("Wrong passphrase or corrupted vault")]
+    WrongPassphrase,
+    #[error("Unsupported vault version: {0}")]
+    UnsupportedVersion(u32),
+    #[error("Passphrase must be at least {MIN_PASSPHRASE_LENGTH} characters long")]
+    WeakPassphrase,
+    #[error("Key derivation error: {0}")]
+    KeyDerivation(argon2::Error),
+    #[error("Encryption error")]
+    Encryption,
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("Storage error: {0}")]
+    Storage(#[from] storage::Error),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Credentials {
+    pub profile_name: String,
+    pub username: String,
+    pub password: String,
+}
+
+impl Credentials {
+    fn is_same_account(&self, other: &Credentials) -> bool {
+        self.profile_name == other.profile_name && self.username == other.username
+    }
+}
+
+/// Key derivation settings, stored so a vault stays readable when the defaults change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct KdfParams {
+    algorithm: String,
+    version: u32,
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+}
+
+impl KdfParams {
+    // The argon2 0.5 defaults
+    fn get_current() -> Self {
+        Self {
+            algorithm: "argon2id".to_string(),
+            version: 0x13,
+            m_cost: 19 * 1024,
+            t_cost: 2,
+            p_cost: 1,
+        }
+    }
+
+    fn get_argon2(&self) -> Result<Argon2<'static>, Error> {
+        let algorithm: Algorithm = self.algorithm.parse().map_err(Error::KeyDerivation)?;
+        let version = Version::try_from(self.version).map_err(Error::KeyDerivation)?;
+        let params = Params::new(self.m_cost, self.t_cost, self.p_cost, Some(KEY_LENGTH))
+            .map_err(Error::KeyDerivation)?;
+
+        Ok(Argon2::new(algorithm, version, params))
+    }
+}
+
+// Only the salt, nonce and key derivation settings are kept in the clear,
+// everything else is in the ciphertext
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    kdf: KdfParams,
+    salt: Vec<u8>,
+    nonce: Vec<u8>,
+    ciphertext: Vec<u8>,
+}
+
+pub fn exists() -> bool {
+    storage::get_path(VAULT_FILE_NAME)
+        .map(|path| path.exists())
+        .unwrap_or(false)
+}
+
+pub fn unlock(passphrase: &str) -> Result<Vec<Credentials>, Error> {
+    let vault_file: VaultFile = storage::load(VAULT_FILE_NAME)?.ok_or(Error::NotFound)?;
+
+    decrypt(passphrase, &vault_file)
+}
+
+/// Adds or replaces the credentials, creating the vault on first use
+pub fn remember(passphrase: &str, credentials: Credentials) -> Result<Vec<Credentials>, Error> {
+    let mut stored_credentials = match unlock(passphrase) {
+        Ok(stored_credentials) => stored_credentials,
+        Err(Error::NotFound) => Vec::new(),
+        Err(error) => return Err(error),
+    };
+
+    stored_credentials.retain(|stored| !stored.is_same_account(&credentials));
+    stored_credentials.push(credentials);
+
+    store(passphrase, &stored_credentials)?;
+
+    Ok(stored_credentials)
+}
+
+/// Re-encrypts the vault under a new passphrase with a fresh salt and nonce
+pub fn change_passphrase(passphrase: &str, new_passphrase: &str) -> Result<(), Error> {
+    let stored_credentials = unlock(passphrase)?;
+
+    store(new_passphrase, &stored_credentials)
+}
+
+pub fn forget() -> Result<(), Error> {
+    Ok(storage::remove(VAULT_FILE_NAME)?)
+}
+
+fn store(passphrase: &str, credentials: &[Credentials]) -> Result<(), Error> {
+    let vault_file = encrypt(passphrase, credentials, KdfParams::get_current())?;
+
+    Ok(storage::save(VAULT_FILE_NAME, &vault_file)?)
+}
+
+fn encrypt(
+    passphrase: &str,
+    credentials: &[Credentials],
+    kdf: KdfParams,
+) -> Result<VaultFile, Error> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LENGTH {
+        return Err(Error::WeakPassphrase);
+    }
+
+    let mut salt = vec![0u8; SALT_LENGTH];
+    OsRng.fill_bytes(&mut salt);
+
+    let cipher = get_cipher(passphrase, &salt, &kdf)?;
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let plaintext = Zeroizing::new(serde_json::to_vec(credentials)?);
+    let associated_data = get_associated_data(VAULT_VERSION, &kdf)?;
+
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: &plaintext,
+                aad: &associated_data,
+            },
+        )
+        .map_err(|_| Error::Encryption)?;
+
+    Ok(VaultFile {
+        version: VAULT_VERSION,
+        kdf,
+        salt,
+        nonce: nonce.to_vec(),
+        ciphertext,
+    })
+}
+
+fn decrypt(passphrase: &str, vault_file: &VaultFile) -> Result<Vec<Credentials>, Error> {
+    if vault_file.version != VAULT_VERSION {
+        return Err(Error::UnsupportedVersion(vault_file.version));
+    }
+
+    if vault_file.nonce.len() != XNonce::default().len() {
+        return Err(Error::WrongPassphrase);
+    }
+
+    let cipher = get_cipher(passphrase, &vault_file.salt, &vault_file.kdf)?;
+    let associated_data = get_associated_data(vault_file.version, &vault_file.kdf)?;
+    let plaintext = cipher
+        .decrypt(
+            XNonce::from_slice(&vault_file.nonce),
+            Payload {
+                msg: &vault_file.ciphertext,
+                aad: &associated_data,
+            },
+        )
+        .map(Zeroizing::new)
+        .map_err(|_| Error::WrongPassphrase)?;
+
+    Ok(serde_json::from_slice(&plaintext)?)
+}
+
+fn get_cipher(passphrase: &str, salt: &[u8], kdf: &KdfParams) -> Result<XChaCha20Poly1305, Error> {
+    let mut key = Zeroizing::new([0u8; KEY_LENGTH]);
+
+    kdf.get_argon2()?
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut())
+        .map_err(Error::KeyDerivation)?;
+
+    Ok(XChaCha20Poly1305::new(Key::from_slice(key.as_ref())))
+}
+
+// Binds the clear-text header to the ciphertext, so a vault with edited
+// key derivation settings fails to open instead of being re-saved weaker
+fn get_associated_data(version: u32, kdf: &KdfParams) -> Result<Vec<u8>, Error> {
+    #[derive(Serialize)]
+    struct Header<'a> {
+        version: u32,
+        kdf: &'a KdfParams,
+    }
+
+    Ok(serde_json::to_vec(&Header { version, kdf })?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cheap parameters keep the tests fast and show they are read back from the file
+    fn get_test_kdf() -> KdfParams {
+        KdfParams {
+            algorithm: "argon2id".to_string(),
+            version: 0x13,
+            m_cost: 64,
+            t_cost: 1,
+            p_cost: 1,
+        }
+    }
+
+    fn get_credentials() -> Vec<Credentials> {
+        vec![Credentials {
+            profile_name: "default".to_string(),
+            username: "alice".to_string(),
+            password: "secret password".to_string(),
+        }]
+    }
+
+    #[test]
+    fn round_trip() {
+        let credentials = get_credentials();
+        let vault_file = encrypt("correct passphrase", &credentials, get_test_kdf()).unwrap();
+
+        assert_eq!(vault_file.kdf, get_test_kdf());
+        assert_eq!(
+            decrypt("correct passphrase", &vault_file).unwrap(),
+            credentials
+        );
+    }
+
+    #[test]
+    fn wrong_passphrase_fails() {
+        let vault_file = encrypt("correct passphrase", &get_credentials(), get_test_kdf()).unwrap();
+
+        assert!(matches!(
+            decrypt("wrong passphrase", &vault_file),
+            Err(Error::WrongPassphrase)
+        ));
+    }
+
+    #[test]
+    fn round_trip_through_json() {
+        let credentials = get_credentials();
+        let vault_file = encrypt("correct passphrase", &credentials, get_test_kdf()).unwrap();
+        let vault_file: VaultFile =
+            serde_json::from_str(&serde_json::to_string(&vault_file).unwrap()).unwrap();
+
+        assert_eq!(
+            decrypt("correct passphrase", &vault_file).unwrap(),
+            credentials
+        );
+    }
+
+    #[test]
+    fn edited_header_fails() {
+        let mut vault_file =
+            encrypt("correct passphrase", &get_credentials(), get_test_kdf()).unwrap();
+        vault_file.kdf.algorithm = "argon2i".to_string();
+
+        assert!(matches!(
+            decrypt("correct passphrase", &vault_file),
+            Err(Error::WrongPassphrase)
+        ));
+    }
+}